@@ -0,0 +1,118 @@
+use core::fmt;
+
+/// An ArbOS version. Behavior that changed across upgrades is gated on the
+/// version the chain is running at the block in question.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ArbOsVersion(pub u64);
+
+impl ArbOsVersion {
+    pub const V1: Self = Self(1);
+    /// Tracks the L1 pricer's last surplus.
+    pub const V2: Self = Self(2);
+    /// Per-batch gas cost and the amortized cost cap.
+    pub const V3: Self = Self(3);
+    /// Infra fee account.
+    pub const V5: Self = Self(5);
+    /// Raised speed limit, block gas limit and L1 equilibration units.
+    pub const V6: Self = Self(6);
+    /// L1 fees available tracked separately from the pool balance.
+    pub const V10: Self = Self(10);
+    /// Infra fee split of the base fee.
+    pub const V11: Self = Self(11);
+    pub const V12: Self = Self(12);
+    /// Atlas: brotli compression level and scheduled upgrades.
+    pub const V20: Self = Self(20);
+    /// Stylus.
+    pub const V30: Self = Self(30);
+    pub const V31: Self = Self(31);
+    pub const V32: Self = Self(32);
+    /// Calldata price increase.
+    pub const V40: Self = Self(40);
+    /// Native token management.
+    pub const V41: Self = Self(41);
+    /// Per-transaction gas limit.
+    pub const V50: Self = Self(50);
+
+    pub const ATLAS: Self = Self::V20;
+    pub const STYLUS: Self = Self::V30;
+
+    pub const fn new(version: u64) -> Self {
+        Self(version)
+    }
+
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    pub const fn is_at_least(self, other: Self) -> bool {
+        self.0 >= other.0
+    }
+
+    pub const fn has_infra_fees(self) -> bool {
+        self.is_at_least(Self::V11)
+    }
+
+    pub const fn has_stylus(self) -> bool {
+        self.is_at_least(Self::STYLUS)
+    }
+
+    pub const fn has_calldata_price_increase(self) -> bool {
+        self.is_at_least(Self::V40)
+    }
+
+    pub const fn has_per_tx_gas_limit(self) -> bool {
+        self.is_at_least(Self::V50)
+    }
+
+    /// The brotli level ArbOS compresses with unless the chain owner changed it.
+    pub const fn default_brotli_compression_level(self) -> u32 {
+        if self.is_at_least(Self::ATLAS) {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl From<u64> for ArbOsVersion {
+    fn from(version: u64) -> Self {
+        Self(version)
+    }
+}
+
+impl From<ArbOsVersion> for u64 {
+    fn from(version: ArbOsVersion) -> Self {
+        version.0
+    }
+}
+
+impl fmt::Display for ArbOsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ArbOS {}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_order_numerically() {
+        assert!(ArbOsVersion::V11 > ArbOsVersion::V10);
+        assert!(ArbOsVersion::new(31).is_at_least(ArbOsVersion::STYLUS));
+        assert!(!ArbOsVersion::V20.is_at_least(ArbOsVersion::STYLUS));
+        assert_eq!(ArbOsVersion::from(20u64), ArbOsVersion::ATLAS);
+        assert_eq!(u64::from(ArbOsVersion::V32), 32);
+    }
+
+    #[test]
+    fn feature_gates_follow_upgrades() {
+        assert!(!ArbOsVersion::V10.has_infra_fees());
+        assert!(ArbOsVersion::V11.has_infra_fees());
+        assert!(ArbOsVersion::V30.has_stylus());
+        assert!(!ArbOsVersion::V32.has_calldata_price_increase());
+        assert!(ArbOsVersion::V40.has_calldata_price_increase());
+        assert_eq!(ArbOsVersion::V11.default_brotli_compression_level(), 0);
+        assert_eq!(ArbOsVersion::V20.default_brotli_compression_level(), 1);
+    }
+}
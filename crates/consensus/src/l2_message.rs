@@ -0,0 +1,445 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use alloy_consensus::TxEnvelope;
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{Address, Bytes, B256, U256};
+
+use crate::messages::{
+    parse_unsigned_tx_fields, sub_request_id, unsigned_tx_envelope, MessageError, Reader,
+    L2_MESSAGE_KIND_CONTRACT_TX, L2_MESSAGE_KIND_UNSIGNED_USER_TX, MAX_L2_MESSAGE_SIZE,
+};
+use crate::{ArbOsVersion, ArbTxEnvelope};
+
+pub const L2_MESSAGE_KIND_NONMUTATING_CALL: u8 = 2;
+pub const L2_MESSAGE_KIND_BATCH: u8 = 3;
+pub const L2_MESSAGE_KIND_SIGNED_TX: u8 = 4;
+pub const L2_MESSAGE_KIND_HEARTBEAT: u8 = 6;
+pub const L2_MESSAGE_KIND_SIGNED_COMPRESSED_TX: u8 = 7;
+
+pub const DEFAULT_MAX_L2_MESSAGE_DEPTH: usize = 16;
+/// Heartbeat messages are rejected from 2022-09-01 UTC on.
+pub const HEARTBEATS_DISABLED_AT: u64 = 1_661_990_400;
+
+const ACCESS_LIST_TX_TYPE: u8 = 1;
+const DYNAMIC_FEE_TX_TYPE: u8 = 2;
+const SET_CODE_TX_TYPE: u8 = 4;
+
+/// A transaction derived from an L2 message. Signed user transactions are
+/// standard Ethereum transactions and are kept in their binary encoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum L2Transaction {
+    Arbitrum(ArbTxEnvelope),
+    Signed(Bytes),
+}
+
+impl L2Transaction {
+    pub fn as_arb(&self) -> Option<&ArbTxEnvelope> {
+        match self {
+            L2Transaction::Arbitrum(tx) => Some(tx),
+            L2Transaction::Signed(_) => None,
+        }
+    }
+
+    pub fn into_arb(self) -> Option<ArbTxEnvelope> {
+        match self {
+            L2Transaction::Arbitrum(tx) => Some(tx),
+            L2Transaction::Signed(_) => None,
+        }
+    }
+}
+
+impl From<ArbTxEnvelope> for L2Transaction {
+    fn from(tx: ArbTxEnvelope) -> Self {
+        L2Transaction::Arbitrum(tx)
+    }
+}
+
+/// Expands L2 messages into transactions the way Nitro's `parseL2Message`
+/// does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct L2MessageParser {
+    pub chain_id: U256,
+    /// The ArbOS version of the parent block, which gates signed tx types.
+    pub arbos_version: ArbOsVersion,
+    pub max_depth: usize,
+    pub max_size: usize,
+}
+
+impl L2MessageParser {
+    pub fn new(chain_id: U256, arbos_version: ArbOsVersion) -> Self {
+        Self {
+            chain_id,
+            arbos_version,
+            max_depth: DEFAULT_MAX_L2_MESSAGE_DEPTH,
+            max_size: MAX_L2_MESSAGE_SIZE,
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn parse(
+        &self,
+        data: &[u8],
+        poster: Address,
+        timestamp: u64,
+        request_id: Option<B256>,
+    ) -> Result<Vec<L2Transaction>, MessageError> {
+        if data.len() > self.max_size {
+            return Err(MessageError::MessageTooLarge);
+        }
+        let mut out = Vec::new();
+        self.parse_into(data, poster, timestamp, request_id, 0, &mut out)?;
+        Ok(out)
+    }
+
+    fn parse_into(
+        &self,
+        data: &[u8],
+        poster: Address,
+        timestamp: u64,
+        request_id: Option<B256>,
+        depth: usize,
+        out: &mut Vec<L2Transaction>,
+    ) -> Result<(), MessageError> {
+        let mut rd = Reader::new(data);
+        let kind = rd.u8()?;
+        match kind {
+            L2_MESSAGE_KIND_UNSIGNED_USER_TX | L2_MESSAGE_KIND_CONTRACT_TX => {
+                let fields = parse_unsigned_tx_fields(&mut rd, kind)?;
+                let tx = unsigned_tx_envelope(kind, fields, poster, request_id, self.chain_id)?;
+                out.push(tx.into());
+            }
+            L2_MESSAGE_KIND_BATCH => {
+                if depth >= self.max_depth {
+                    return Err(MessageError::MaxDepthExceeded(self.max_depth));
+                }
+                let mut index = U256::ZERO;
+                // A segment that is oversized or cut short ends the batch.
+                while let Some(segment) = self.next_segment(&mut rd) {
+                    let sub_request_id = request_id.map(|id| sub_request_id(id, index));
+                    self.parse_into(segment, poster, timestamp, sub_request_id, depth + 1, out)?;
+                    index += U256::from(1);
+                }
+            }
+            L2_MESSAGE_KIND_SIGNED_TX => {
+                let tx = rd.rest();
+                self.check_signed_tx_type(tx)?;
+                decode_signed_tx(tx)?;
+                out.push(L2Transaction::Signed(Bytes::copy_from_slice(tx)));
+            }
+            // Heartbeats produce nothing until they were disabled.
+            L2_MESSAGE_KIND_HEARTBEAT => {
+                if timestamp >= HEARTBEATS_DISABLED_AT {
+                    return Err(MessageError::HeartbeatsDisabled);
+                }
+            }
+            L2_MESSAGE_KIND_NONMUTATING_CALL | L2_MESSAGE_KIND_SIGNED_COMPRESSED_TX => {
+                return Err(MessageError::UnimplementedL2Kind(kind));
+            }
+            _ => return Err(MessageError::UnknownL2Kind(kind)),
+        }
+        Ok(())
+    }
+
+    fn next_segment<'a>(&self, rd: &mut Reader<'a>) -> Option<&'a [u8]> {
+        let len = rd.u64_be().ok()?;
+        if len > self.max_size as u64 {
+            return None;
+        }
+        rd.take(len as usize).ok()
+    }
+
+    fn check_signed_tx_type(&self, tx: &[u8]) -> Result<(), MessageError> {
+        let Some(&first) = tx.first() else {
+            return Err(MessageError::Truncated);
+        };
+        // Legacy transactions are a bare RLP list.
+        if first >= 0xc0 {
+            return Ok(());
+        }
+        match first {
+            ACCESS_LIST_TX_TYPE | DYNAMIC_FEE_TX_TYPE => Ok(()),
+            SET_CODE_TX_TYPE if self.arbos_version.is_at_least(ArbOsVersion::V40) => Ok(()),
+            // Blob txs, Arbitrum types and unknown types are rejected.
+            _ => Err(MessageError::UnsupportedSignedTxType(first)),
+        }
+    }
+}
+
+/// Nitro's `UnmarshalBinary`: the whole payload must be one valid envelope.
+fn decode_signed_tx(tx: &[u8]) -> Result<TxEnvelope, MessageError> {
+    let mut buf = tx;
+    let envelope = TxEnvelope::decode_2718(&mut buf).map_err(|_| MessageError::InvalidSignedTx)?;
+    if !buf.is_empty() {
+        return Err(MessageError::InvalidSignedTx);
+    }
+    Ok(envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{SignableTransaction, TxEip1559, TxEip7702, TxLegacy};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_primitives::Signature;
+
+    fn parser() -> L2MessageParser {
+        L2MessageParser::new(U256::from(42161u64), ArbOsVersion::V32)
+    }
+
+    fn word(v: u64) -> [u8; 32] {
+        U256::from(v).to_be_bytes::<32>()
+    }
+
+    fn contract_tx(value: u64) -> Vec<u8> {
+        let mut msg = alloc::vec![L2_MESSAGE_KIND_CONTRACT_TX];
+        msg.extend_from_slice(&word(50_000));
+        msg.extend_from_slice(&word(100));
+        msg.extend_from_slice(&word(0));
+        msg.extend_from_slice(&word(value));
+        msg
+    }
+
+    fn batch(segments: &[Vec<u8>]) -> Vec<u8> {
+        let mut msg = alloc::vec![L2_MESSAGE_KIND_BATCH];
+        for seg in segments {
+            msg.extend_from_slice(&(seg.len() as u64).to_be_bytes());
+            msg.extend_from_slice(seg);
+        }
+        msg
+    }
+
+    #[test]
+    fn unsigned_user_tx_carries_nonce() {
+        let mut msg = alloc::vec![L2_MESSAGE_KIND_UNSIGNED_USER_TX];
+        msg.extend_from_slice(&word(21_000));
+        msg.extend_from_slice(&word(100));
+        msg.extend_from_slice(&word(9));
+        msg.extend_from_slice(&word(0x55));
+        msg.extend_from_slice(&word(1));
+        let txs = parser()
+            .parse(&msg, Address::with_last_byte(1), 0, None)
+            .unwrap();
+        let Some(ArbTxEnvelope::Unsigned(tx)) = txs[0].as_arb() else {
+            panic!("expected unsigned tx");
+        };
+        assert_eq!(tx.nonce, 9);
+        assert_eq!(tx.from, Address::with_last_byte(1));
+        assert_eq!(tx.to, Some(Address::with_last_byte(0x55)));
+        assert_eq!(tx.chain_id, U256::from(42161u64));
+    }
+
+    #[test]
+    fn contract_tx_needs_request_id() {
+        let poster = Address::with_last_byte(1);
+        assert_eq!(
+            parser().parse(&contract_tx(1), poster, 0, None),
+            Err(MessageError::MissingRequestId)
+        );
+        let txs = parser()
+            .parse(&contract_tx(1), poster, 0, Some(B256::repeat_byte(1)))
+            .unwrap();
+        let Some(ArbTxEnvelope::Contract(tx)) = txs[0].as_arb() else {
+            panic!("expected contract tx");
+        };
+        assert_eq!(tx.request_id, B256::repeat_byte(1));
+        assert_eq!(tx.to, None);
+    }
+
+    #[test]
+    fn batches_derive_sub_request_ids_in_order() {
+        let request_id = B256::repeat_byte(7);
+        let nested = batch(&[contract_tx(3)]);
+        let msg = batch(&[contract_tx(1), contract_tx(2), nested]);
+        let txs = parser()
+            .parse(&msg, Address::with_last_byte(1), 0, Some(request_id))
+            .unwrap();
+        let ids: Vec<_> = txs
+            .iter()
+            .map(|tx| match tx.as_arb() {
+                Some(ArbTxEnvelope::Contract(c)) => (c.value, c.request_id),
+                _ => panic!("expected contract tx"),
+            })
+            .collect();
+        assert_eq!(
+            ids,
+            alloc::vec![
+                (U256::from(1u64), sub_request_id(request_id, U256::ZERO)),
+                (U256::from(2u64), sub_request_id(request_id, U256::from(1))),
+                (
+                    U256::from(3u64),
+                    sub_request_id(sub_request_id(request_id, U256::from(2)), U256::ZERO)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_segment_ends_batch() {
+        let mut msg = batch(&[contract_tx(1)]);
+        msg.extend_from_slice(&100u64.to_be_bytes());
+        msg.extend_from_slice(&[1, 2, 3]);
+        let txs = parser()
+            .parse(&msg, Address::ZERO, 0, Some(B256::ZERO))
+            .unwrap();
+        assert_eq!(txs.len(), 1);
+    }
+
+    #[test]
+    fn nesting_depth_is_limited() {
+        let mut msg = contract_tx(1);
+        for _ in 0..3 {
+            msg = batch(&[msg]);
+        }
+        let id = Some(B256::ZERO);
+        assert_eq!(parser().parse(&msg, Address::ZERO, 0, id).unwrap().len(), 1);
+        assert_eq!(
+            parser().with_max_depth(2).parse(&msg, Address::ZERO, 0, id),
+            Err(MessageError::MaxDepthExceeded(2))
+        );
+    }
+
+    #[test]
+    fn size_limit_applies() {
+        let msg = contract_tx(1);
+        assert_eq!(
+            parser()
+                .with_max_size(msg.len() - 1)
+                .parse(&msg, Address::ZERO, 0, Some(B256::ZERO)),
+            Err(MessageError::MessageTooLarge)
+        );
+    }
+
+    fn signed(tx: TxEnvelope) -> Vec<u8> {
+        let mut msg = alloc::vec![L2_MESSAGE_KIND_SIGNED_TX];
+        msg.extend_from_slice(&tx.encoded_2718());
+        msg
+    }
+
+    fn legacy_tx() -> Vec<u8> {
+        let tx = TxLegacy {
+            chain_id: Some(42161),
+            gas_limit: 21_000,
+            ..Default::default()
+        };
+        signed(tx.into_signed(Signature::test_signature()).into())
+    }
+
+    #[test]
+    fn signed_txs_pass_through_by_type() {
+        let p = parser();
+        let legacy = legacy_tx();
+        assert_eq!(
+            p.parse(&legacy, Address::ZERO, 0, None).unwrap(),
+            alloc::vec![L2Transaction::Signed(Bytes::copy_from_slice(&legacy[1..]))]
+        );
+        let dynamic_fee = TxEip1559 {
+            chain_id: 42161,
+            gas_limit: 21_000,
+            ..Default::default()
+        };
+        let dynamic_fee = signed(dynamic_fee.into_signed(Signature::test_signature()).into());
+        assert!(p.parse(&dynamic_fee, Address::ZERO, 0, None).is_ok());
+        assert_eq!(
+            p.parse(
+                &[L2_MESSAGE_KIND_SIGNED_TX, 3, 0xc0],
+                Address::ZERO,
+                0,
+                None
+            ),
+            Err(MessageError::UnsupportedSignedTxType(3))
+        );
+        assert_eq!(
+            p.parse(
+                &[L2_MESSAGE_KIND_SIGNED_TX, 0x64, 0xc0],
+                Address::ZERO,
+                0,
+                None
+            ),
+            Err(MessageError::UnsupportedSignedTxType(0x64))
+        );
+        let set_code = TxEip7702 {
+            chain_id: 42161,
+            gas_limit: 50_000,
+            ..Default::default()
+        };
+        let set_code = signed(set_code.into_signed(Signature::test_signature()).into());
+        assert_eq!(
+            p.parse(&set_code, Address::ZERO, 0, None),
+            Err(MessageError::UnsupportedSignedTxType(4))
+        );
+        for version in [ArbOsVersion::V40, ArbOsVersion::V50] {
+            let p = L2MessageParser::new(U256::from(1), version);
+            assert!(p.parse(&set_code, Address::ZERO, 0, None).is_ok());
+        }
+    }
+
+    #[test]
+    fn malformed_signed_tx_rejects_the_message() {
+        let p = parser();
+        let legacy = legacy_tx();
+        assert_eq!(
+            p.parse(
+                &[L2_MESSAGE_KIND_SIGNED_TX, 2, 0xc0],
+                Address::ZERO,
+                0,
+                None
+            ),
+            Err(MessageError::InvalidSignedTx)
+        );
+        let mut trailing = legacy.clone();
+        trailing.push(0);
+        assert_eq!(
+            p.parse(&trailing, Address::ZERO, 0, None),
+            Err(MessageError::InvalidSignedTx)
+        );
+
+        // A truncated tx takes its well-formed siblings down with it.
+        let truncated = legacy[..legacy.len() - 1].to_vec();
+        let msg = batch(&[legacy.clone(), truncated]);
+        assert_eq!(
+            p.parse(&msg, Address::ZERO, 0, None),
+            Err(MessageError::InvalidSignedTx)
+        );
+        assert_eq!(
+            p.parse(&batch(&[legacy.clone(), legacy]), Address::ZERO, 0, None)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn heartbeat_and_unimplemented_kinds() {
+        let p = parser();
+        let heartbeat =
+            |timestamp| p.parse(&[L2_MESSAGE_KIND_HEARTBEAT], Address::ZERO, timestamp, None);
+        assert_eq!(heartbeat(HEARTBEATS_DISABLED_AT - 1), Ok(Vec::new()));
+        assert_eq!(
+            heartbeat(HEARTBEATS_DISABLED_AT),
+            Err(MessageError::HeartbeatsDisabled)
+        );
+        assert_eq!(
+            p.parse(&[L2_MESSAGE_KIND_NONMUTATING_CALL], Address::ZERO, 0, None),
+            Err(MessageError::UnimplementedL2Kind(2))
+        );
+        assert_eq!(
+            p.parse(&[5], Address::ZERO, 0, None),
+            Err(MessageError::UnknownL2Kind(5))
+        );
+        assert_eq!(
+            p.parse(&[], Address::ZERO, 0, None),
+            Err(MessageError::Truncated)
+        );
+    }
+}
@@ -1,5 +1,13 @@
+pub mod arbos_version;
+pub mod l2_message;
+pub mod messages;
 pub mod receipt;
+pub mod sequencer_batch;
 pub mod tx;
 
+pub use arbos_version::ArbOsVersion;
+pub use l2_message::{L2MessageParser, L2Transaction};
+pub use messages::{L1IncomingMessage, L1MessageKind};
 pub use receipt::ArbReceiptEnvelope;
+pub use sequencer_batch::BatchHeader;
 pub use tx::{ArbTxEnvelope, ArbTxType};
@@ -0,0 +1,798 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use thiserror::Error;
+
+use crate::l2_message::{L2MessageParser, L2Transaction};
+use crate::tx::{
+    ArbContractTx, ArbDepositTx, ArbInternalTx, ArbSubmitRetryableTx, ArbTxEnvelope, ArbUnsignedTx,
+};
+
+pub const MAX_L2_MESSAGE_SIZE: usize = 256 * 1024;
+pub const DEFAULT_INITIAL_L1_BASE_FEE: u64 = 50_000_000_000;
+
+/// Length of the serialized `L1IncomingMessageHeader`.
+pub const L1_MESSAGE_HEADER_LEN: usize = 1 + 32 + 8 + 8 + 32 + 32;
+
+pub const L2_MESSAGE_KIND_UNSIGNED_USER_TX: u8 = 0;
+pub const L2_MESSAGE_KIND_CONTRACT_TX: u8 = 1;
+
+const TX_DATA_ZERO_GAS: u64 = 4;
+const TX_DATA_NON_ZERO_GAS_EIP2028: u64 = 16;
+const KECCAK256_GAS: u64 = 30;
+const KECCAK256_WORD_GAS: u64 = 6;
+const SSTORE_SET_GAS_EIP2200: u64 = 20_000;
+
+const BATCH_POSTING_REPORT_SIG: &str = "batchPostingReport(uint256,address,uint64,uint64,uint256)";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum L1MessageKind {
+    L2Message = 3,
+    EndOfBlock = 6,
+    L2FundedByL1 = 7,
+    RollupEvent = 8,
+    SubmitRetryable = 9,
+    BatchForGasEstimation = 10,
+    Initialize = 11,
+    EthDeposit = 12,
+    BatchPostingReport = 13,
+    Invalid = 0xff,
+}
+
+impl L1MessageKind {
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+    pub fn from_u8(b: u8) -> Result<Self, MessageError> {
+        match b {
+            3 => Ok(Self::L2Message),
+            6 => Ok(Self::EndOfBlock),
+            7 => Ok(Self::L2FundedByL1),
+            8 => Ok(Self::RollupEvent),
+            9 => Ok(Self::SubmitRetryable),
+            10 => Ok(Self::BatchForGasEstimation),
+            11 => Ok(Self::Initialize),
+            12 => Ok(Self::EthDeposit),
+            13 => Ok(Self::BatchPostingReport),
+            0xff => Ok(Self::Invalid),
+            _ => Err(MessageError::UnknownKind(b)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct L1IncomingMessageHeader {
+    pub kind: u8,
+    pub poster: Address,
+    pub block_number: u64,
+    pub timestamp: u64,
+    /// Set for delayed messages; sequencer messages carry none.
+    pub request_id: Option<B256>,
+    pub l1_base_fee: U256,
+}
+
+/// A message from the delayed inbox or a sequencer batch, in Nitro's
+/// serialization: the header fields followed by the kind-specific payload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct L1IncomingMessage {
+    pub header: L1IncomingMessageHeader,
+    pub l2_msg: Bytes,
+}
+
+/// Fields shared by unsigned user txs and contract txs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsignedTxFields {
+    pub gas_limit: u64,
+    pub max_fee_per_gas: U256,
+    /// Only present for unsigned user txs.
+    pub nonce: Option<u64>,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubmitRetryableFields {
+    pub retry_to: Option<Address>,
+    pub call_value: U256,
+    pub deposit_value: U256,
+    pub max_submission_fee: U256,
+    pub fee_refund_address: Address,
+    pub callvalue_refund_address: Address,
+    pub max_gas: u64,
+    pub gas_fee_cap: U256,
+    pub data: Bytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitMessage {
+    pub chain_id: U256,
+    pub initial_l1_base_fee: U256,
+    pub serialized_chain_config: Bytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchPostingReport {
+    pub batch_timestamp: U256,
+    pub batch_poster: Address,
+    pub data_hash: B256,
+    pub batch_number: u64,
+    pub l1_base_fee: U256,
+    pub extra_gas: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum L1MessageBody {
+    L2Message(Bytes),
+    EndOfBlock,
+    L2FundedByL1 { kind: u8, tx: UnsignedTxFields },
+    RollupEvent(Bytes),
+    SubmitRetryable(SubmitRetryableFields),
+    BatchForGasEstimation(Bytes),
+    Initialize(InitMessage),
+    EthDeposit { to: Address, value: U256 },
+    BatchPostingReport(BatchPostingReport),
+    Invalid,
+}
+
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], MessageError> {
+        if self.buf.len() < n {
+            return Err(MessageError::Truncated);
+        }
+        let (head, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(head)
+    }
+    pub(crate) fn u8(&mut self) -> Result<u8, MessageError> {
+        Ok(self.take(1)?[0])
+    }
+    pub(crate) fn u64_be(&mut self) -> Result<u64, MessageError> {
+        let mut b = [0u8; 8];
+        b.copy_from_slice(self.take(8)?);
+        Ok(u64::from_be_bytes(b))
+    }
+    pub(crate) fn hash(&mut self) -> Result<B256, MessageError> {
+        Ok(B256::from_slice(self.take(32)?))
+    }
+    pub(crate) fn u256(&mut self) -> Result<U256, MessageError> {
+        Ok(U256::from_be_slice(self.take(32)?))
+    }
+    pub(crate) fn u256_as_u64(&mut self, what: &'static str) -> Result<u64, MessageError> {
+        u64::try_from(self.u256()?).map_err(|_| MessageError::ValueTooLarge(what))
+    }
+    pub(crate) fn address(&mut self) -> Result<Address, MessageError> {
+        Ok(Address::from_slice(self.take(20)?))
+    }
+    pub(crate) fn address_from_256(&mut self) -> Result<Address, MessageError> {
+        Ok(Address::from_slice(&self.take(32)?[12..]))
+    }
+    /// Go's `bytes.Reader.Read` into a zeroed `n`-byte buffer: copies what
+    /// is left and only fails when nothing is.
+    pub(crate) fn read_zero_padded(&mut self, n: usize) -> Result<Vec<u8>, MessageError> {
+        let mut out = alloc::vec![0u8; n];
+        if n == 0 {
+            return Ok(out);
+        }
+        if self.buf.is_empty() {
+            return Err(MessageError::Truncated);
+        }
+        let read = n.min(self.buf.len());
+        out[..read].copy_from_slice(self.take(read)?);
+        Ok(out)
+    }
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        core::mem::take(&mut self.buf)
+    }
+}
+
+fn non_zero(addr: Address) -> Option<Address> {
+    (addr != Address::ZERO).then_some(addr)
+}
+
+fn address_to_256(addr: Address, out: &mut Vec<u8>) {
+    out.extend_from_slice(&[0u8; 12]);
+    out.extend_from_slice(addr.as_slice());
+}
+
+/// Nitro's `ComputeBatchGasCost`: the L1 gas a poster spends on a batch.
+pub fn compute_batch_gas_cost(data: &[u8]) -> u64 {
+    let mut gas = data.iter().fold(0u64, |acc, &b| {
+        acc + if b == 0 {
+            TX_DATA_ZERO_GAS
+        } else {
+            TX_DATA_NON_ZERO_GAS_EIP2028
+        }
+    });
+    let keccak_words = (data.len() as u64).div_ceil(32);
+    gas += KECCAK256_GAS + keccak_words * KECCAK256_WORD_GAS;
+    gas += 2 * SSTORE_SET_GAS_EIP2200;
+    gas
+}
+
+/// The request ID of the `index`th transaction derived from a message.
+pub fn sub_request_id(request_id: B256, index: U256) -> B256 {
+    let mut input = [0u8; 64];
+    input[..32].copy_from_slice(request_id.as_slice());
+    input[32..].copy_from_slice(&index.to_be_bytes::<32>());
+    keccak256(input)
+}
+
+pub(crate) fn parse_unsigned_tx_fields(
+    rd: &mut Reader<'_>,
+    kind: u8,
+) -> Result<UnsignedTxFields, MessageError> {
+    let gas_limit = rd.u256_as_u64("gas limit")?;
+    let max_fee_per_gas = rd.u256()?;
+    let nonce = if kind == L2_MESSAGE_KIND_UNSIGNED_USER_TX {
+        Some(rd.u256_as_u64("nonce")?)
+    } else {
+        None
+    };
+    let to = non_zero(rd.address_from_256()?);
+    let value = rd.u256()?;
+    let data = Bytes::copy_from_slice(rd.rest());
+    Ok(UnsignedTxFields {
+        gas_limit,
+        max_fee_per_gas,
+        nonce,
+        to,
+        value,
+        data,
+    })
+}
+
+pub(crate) fn unsigned_tx_envelope(
+    kind: u8,
+    fields: UnsignedTxFields,
+    poster: Address,
+    request_id: Option<B256>,
+    chain_id: U256,
+) -> Result<ArbTxEnvelope, MessageError> {
+    match kind {
+        L2_MESSAGE_KIND_UNSIGNED_USER_TX => Ok(ArbTxEnvelope::Unsigned(ArbUnsignedTx {
+            chain_id,
+            from: poster,
+            nonce: fields.nonce.unwrap_or_default(),
+            gas_fee_cap: fields.max_fee_per_gas,
+            gas: fields.gas_limit,
+            to: fields.to,
+            value: fields.value,
+            data: fields.data,
+        })),
+        L2_MESSAGE_KIND_CONTRACT_TX => Ok(ArbTxEnvelope::Contract(ArbContractTx {
+            chain_id,
+            request_id: request_id.ok_or(MessageError::MissingRequestId)?,
+            from: poster,
+            gas_fee_cap: fields.max_fee_per_gas,
+            gas: fields.gas_limit,
+            to: fields.to,
+            value: fields.value,
+            data: fields.data,
+        })),
+        _ => Err(MessageError::InvalidUnsignedTxKind(kind)),
+    }
+}
+
+impl L1IncomingMessage {
+    pub fn parse(bytes: &[u8]) -> Result<Self, MessageError> {
+        let mut rd = Reader::new(bytes);
+        let header = L1IncomingMessageHeader {
+            kind: rd.u8()?,
+            poster: rd.address_from_256()?,
+            block_number: rd.u64_be()?,
+            timestamp: rd.u64_be()?,
+            request_id: Some(rd.hash()?),
+            l1_base_fee: rd.u256()?,
+        };
+        Ok(Self {
+            header,
+            l2_msg: Bytes::copy_from_slice(rd.rest()),
+        })
+    }
+
+    pub fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        let request_id = self
+            .header
+            .request_id
+            .ok_or(MessageError::MissingRequestId)?;
+        let mut out = Vec::with_capacity(L1_MESSAGE_HEADER_LEN + self.l2_msg.len());
+        out.push(self.header.kind);
+        address_to_256(self.header.poster, &mut out);
+        out.extend_from_slice(&self.header.block_number.to_be_bytes());
+        out.extend_from_slice(&self.header.timestamp.to_be_bytes());
+        out.extend_from_slice(request_id.as_slice());
+        out.extend_from_slice(&self.header.l1_base_fee.to_be_bytes::<32>());
+        out.extend_from_slice(&self.l2_msg);
+        Ok(out)
+    }
+
+    pub fn kind(&self) -> Result<L1MessageKind, MessageError> {
+        L1MessageKind::from_u8(self.header.kind)
+    }
+
+    pub fn decode_body(&self) -> Result<L1MessageBody, MessageError> {
+        let mut rd = Reader::new(&self.l2_msg);
+        Ok(match self.kind()? {
+            L1MessageKind::L2Message => L1MessageBody::L2Message(self.l2_msg.clone()),
+            L1MessageKind::EndOfBlock => L1MessageBody::EndOfBlock,
+            L1MessageKind::L2FundedByL1 => {
+                let kind = rd.u8().map_err(|_| MessageError::EmptyL2FundedByL1)?;
+                let tx = parse_unsigned_tx_fields(&mut rd, kind)?;
+                L1MessageBody::L2FundedByL1 { kind, tx }
+            }
+            L1MessageKind::RollupEvent => L1MessageBody::RollupEvent(self.l2_msg.clone()),
+            L1MessageKind::SubmitRetryable => {
+                let retry_to = non_zero(rd.address_from_256()?);
+                let call_value = rd.u256()?;
+                let deposit_value = rd.u256()?;
+                let max_submission_fee = rd.u256()?;
+                let fee_refund_address = rd.address_from_256()?;
+                let callvalue_refund_address = rd.address_from_256()?;
+                let max_gas = rd.u256_as_u64("gas")?;
+                let gas_fee_cap = rd.u256()?;
+                let data_len = rd.u256_as_u64("retryable data length")?;
+                if data_len > MAX_L2_MESSAGE_SIZE as u64 {
+                    return Err(MessageError::MessageTooLarge);
+                }
+                // A short body leaves the tail of the retry data zeroed.
+                let data = Bytes::from(rd.read_zero_padded(data_len as usize)?);
+                L1MessageBody::SubmitRetryable(SubmitRetryableFields {
+                    retry_to,
+                    call_value,
+                    deposit_value,
+                    max_submission_fee,
+                    fee_refund_address,
+                    callvalue_refund_address,
+                    max_gas,
+                    gas_fee_cap,
+                    data,
+                })
+            }
+            L1MessageKind::BatchForGasEstimation => {
+                L1MessageBody::BatchForGasEstimation(self.l2_msg.clone())
+            }
+            L1MessageKind::Initialize => L1MessageBody::Initialize(self.parse_init_message()?),
+            L1MessageKind::EthDeposit => L1MessageBody::EthDeposit {
+                to: rd.address()?,
+                value: rd.u256()?,
+            },
+            L1MessageKind::BatchPostingReport => {
+                let batch_timestamp = rd.u256()?;
+                let batch_poster = rd.address()?;
+                let data_hash = rd.hash()?;
+                let batch_number = rd.u256_as_u64("batch number")?;
+                let l1_base_fee = rd.u256()?;
+                let extra_gas = if rd.is_empty() { 0 } else { rd.u64_be()? };
+                L1MessageBody::BatchPostingReport(BatchPostingReport {
+                    batch_timestamp,
+                    batch_poster,
+                    data_hash,
+                    batch_number,
+                    l1_base_fee,
+                    extra_gas,
+                })
+            }
+            L1MessageKind::Invalid => L1MessageBody::Invalid,
+        })
+    }
+
+    fn parse_init_message(&self) -> Result<InitMessage, MessageError> {
+        let msg = &self.l2_msg;
+        if msg.len() < 32 {
+            return Err(MessageError::InvalidInitMessage);
+        }
+        let chain_id = U256::from_be_slice(&msg[..32]);
+        if msg.len() == 32 {
+            return Ok(InitMessage {
+                chain_id,
+                initial_l1_base_fee: U256::from(DEFAULT_INITIAL_L1_BASE_FEE),
+                serialized_chain_config: Bytes::new(),
+            });
+        }
+        let mut rd = Reader::new(&msg[33..]);
+        let initial_l1_base_fee = match msg[32] {
+            0 => U256::from(DEFAULT_INITIAL_L1_BASE_FEE),
+            1 => rd.u256()?,
+            _ => return Err(MessageError::InvalidInitMessage),
+        };
+        Ok(InitMessage {
+            chain_id,
+            initial_l1_base_fee,
+            serialized_chain_config: Bytes::copy_from_slice(rd.rest()),
+        })
+    }
+
+    /// The transactions Nitro's `ParseL2Transactions` derives from this
+    /// message. `batch_gas_cost` is only consulted for batch posting reports.
+    pub fn to_transactions(
+        &self,
+        parser: &L2MessageParser,
+        batch_gas_cost: Option<u64>,
+    ) -> Result<Vec<L2Transaction>, MessageError> {
+        if self.l2_msg.len() > parser.max_size {
+            return Err(MessageError::MessageTooLarge);
+        }
+        let header = &self.header;
+        let chain_id = parser.chain_id;
+        let request_id = || header.request_id.ok_or(MessageError::MissingRequestId);
+        let tx = match self.decode_body()? {
+            L1MessageBody::L2Message(data) => {
+                return parser.parse(&data, header.poster, header.timestamp, header.request_id)
+            }
+            L1MessageBody::EndOfBlock | L1MessageBody::RollupEvent(_) => return Ok(Vec::new()),
+            L1MessageBody::L2FundedByL1 { kind, tx } => {
+                let request_id = request_id()?;
+                let deposit_request_id = sub_request_id(request_id, U256::ZERO);
+                let unsigned_request_id = sub_request_id(request_id, U256::from(1));
+                let value = tx.value;
+                let tx = unsigned_tx_envelope(
+                    kind,
+                    tx,
+                    header.poster,
+                    Some(unsigned_request_id),
+                    chain_id,
+                )?;
+                let deposit = ArbTxEnvelope::Deposit(ArbDepositTx {
+                    chain_id,
+                    l1_request_id: deposit_request_id,
+                    from: Address::ZERO,
+                    to: header.poster,
+                    value,
+                });
+                return Ok(alloc::vec![deposit.into(), tx.into()]);
+            }
+            L1MessageBody::SubmitRetryable(f) => {
+                ArbTxEnvelope::SubmitRetryable(ArbSubmitRetryableTx {
+                    chain_id,
+                    request_id: request_id()?,
+                    from: header.poster,
+                    l1_base_fee: header.l1_base_fee,
+                    deposit_value: f.deposit_value,
+                    gas_fee_cap: f.gas_fee_cap,
+                    gas: f.max_gas,
+                    retry_to: f.retry_to,
+                    retry_value: f.call_value,
+                    beneficiary: f.callvalue_refund_address,
+                    max_submission_fee: f.max_submission_fee,
+                    fee_refund_addr: f.fee_refund_address,
+                    retry_data: f.data,
+                })
+            }
+            L1MessageBody::EthDeposit { to, value } => ArbTxEnvelope::Deposit(ArbDepositTx {
+                chain_id,
+                l1_request_id: request_id()?,
+                from: header.poster,
+                to,
+                value,
+            }),
+            L1MessageBody::BatchPostingReport(report) => {
+                let batch_data_gas = batch_gas_cost
+                    .ok_or(MessageError::MissingBatchGasCost)?
+                    .saturating_add(report.extra_gas);
+                ArbTxEnvelope::Internal(ArbInternalTx {
+                    chain_id,
+                    data: report.internal_tx_data(batch_data_gas),
+                })
+            }
+            L1MessageBody::Initialize(_)
+            | L1MessageBody::BatchForGasEstimation(_)
+            | L1MessageBody::Invalid => return Err(MessageError::Unsupported(header.kind)),
+        };
+        Ok(alloc::vec![tx.into()])
+    }
+}
+
+impl BatchPostingReport {
+    /// ABI-encoded `batchPostingReport` call carried by the internal tx.
+    pub fn internal_tx_data(&self, batch_data_gas: u64) -> Bytes {
+        let mut out = Vec::with_capacity(4 + 5 * 32);
+        out.extend_from_slice(&keccak256(BATCH_POSTING_REPORT_SIG.as_bytes())[..4]);
+        out.extend_from_slice(&self.batch_timestamp.to_be_bytes::<32>());
+        address_to_256(self.batch_poster, &mut out);
+        out.extend_from_slice(&U256::from(self.batch_number).to_be_bytes::<32>());
+        out.extend_from_slice(&U256::from(batch_data_gas).to_be_bytes::<32>());
+        out.extend_from_slice(&self.l1_base_fee.to_be_bytes::<32>());
+        out.into()
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum MessageError {
+    #[error("unknown L1 message kind: {0}")]
+    UnknownKind(u8),
+    #[error("message truncated")]
+    Truncated,
+    #[error("{0} does not fit in 64 bits")]
+    ValueTooLarge(&'static str),
+    #[error("message too large")]
+    MessageTooLarge,
+    #[error("L2FundedByL1 message has no data")]
+    EmptyL2FundedByL1,
+    #[error("invalid L2 tx kind in unsigned tx: {0}")]
+    InvalidUnsignedTxKind(u8),
+    #[error("cannot issue contract tx without L1 request id")]
+    MissingRequestId,
+    #[error("L2 message batches have a max depth of {0}")]
+    MaxDepthExceeded(usize),
+    #[error("L2 message kind {0} is unimplemented")]
+    UnimplementedL2Kind(u8),
+    #[error("unknown L2 message kind: {0}")]
+    UnknownL2Kind(u8),
+    #[error("signed tx type {0} is not supported")]
+    UnsupportedSignedTxType(u8),
+    #[error("invalid signed tx")]
+    InvalidSignedTx,
+    #[error("heartbeat messages have been disabled")]
+    HeartbeatsDisabled,
+    #[error("invalid init message")]
+    InvalidInitMessage,
+    #[error("cannot compute batch gas cost")]
+    MissingBatchGasCost,
+    #[error("L1 message kind {0} does not produce transactions")]
+    Unsupported(u8),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArbOsVersion;
+    use alloy_primitives::address;
+
+    fn word(v: u64) -> [u8; 32] {
+        U256::from(v).to_be_bytes::<32>()
+    }
+
+    fn addr_word(a: Address) -> Vec<u8> {
+        let mut out = Vec::new();
+        address_to_256(a, &mut out);
+        out
+    }
+
+    fn arb_txs(
+        msg: &L1IncomingMessage,
+        chain_id: u64,
+        batch_gas_cost: Option<u64>,
+    ) -> Result<Vec<ArbTxEnvelope>, MessageError> {
+        let parser = L2MessageParser::new(U256::from(chain_id), ArbOsVersion::V32);
+        Ok(msg
+            .to_transactions(&parser, batch_gas_cost)?
+            .into_iter()
+            .map(|tx| tx.into_arb().expect("arbitrum tx"))
+            .collect())
+    }
+
+    fn message(kind: L1MessageKind, l2_msg: Vec<u8>) -> L1IncomingMessage {
+        L1IncomingMessage {
+            header: L1IncomingMessageHeader {
+                kind: kind.as_u8(),
+                poster: address!("00000000000000000000000000000000000000aa"),
+                block_number: 17,
+                timestamp: 1_700_000_000,
+                request_id: Some(B256::repeat_byte(0x42)),
+                l1_base_fee: U256::from(30_000_000_000u64),
+            },
+            l2_msg: l2_msg.into(),
+        }
+    }
+
+    #[test]
+    fn header_roundtrip() {
+        let msg = message(L1MessageKind::L2Message, alloc::vec![4, 1, 2, 3]);
+        let bytes = msg.serialize().unwrap();
+        assert_eq!(bytes.len(), L1_MESSAGE_HEADER_LEN + 4);
+        assert_eq!(L1IncomingMessage::parse(&bytes).unwrap(), msg);
+        let mut sequenced = msg.clone();
+        sequenced.header.request_id = None;
+        assert_eq!(sequenced.serialize(), Err(MessageError::MissingRequestId));
+        assert_eq!(
+            L1IncomingMessage::parse(&bytes[..L1_MESSAGE_HEADER_LEN - 1]),
+            Err(MessageError::Truncated)
+        );
+    }
+
+    #[test]
+    fn unknown_kind_is_rejected() {
+        let mut msg = message(L1MessageKind::EndOfBlock, Vec::new());
+        msg.header.kind = 5;
+        assert_eq!(msg.kind(), Err(MessageError::UnknownKind(5)));
+        let msg = message(L1MessageKind::EndOfBlock, Vec::new());
+        assert_eq!(arb_txs(&msg, 1, None), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn l2_message_expands_through_parser() {
+        use alloy_consensus::{SignableTransaction, TxEnvelope, TxLegacy};
+        use alloy_eips::eip2718::Encodable2718;
+
+        let tx: TxEnvelope = TxLegacy::default()
+            .into_signed(alloy_primitives::Signature::test_signature())
+            .into();
+        let encoded = tx.encoded_2718();
+        let mut l2_msg = alloc::vec![4];
+        l2_msg.extend_from_slice(&encoded);
+        let msg = message(L1MessageKind::L2Message, l2_msg);
+        let parser = L2MessageParser::new(U256::from(1), ArbOsVersion::V32);
+        assert_eq!(
+            msg.to_transactions(&parser, None),
+            Ok(alloc::vec![L2Transaction::Signed(encoded.into())])
+        );
+        // Heartbeats are judged by the message's own timestamp.
+        let heartbeat = message(L1MessageKind::L2Message, alloc::vec![6]);
+        assert_eq!(
+            heartbeat.to_transactions(&parser, None),
+            Err(MessageError::HeartbeatsDisabled)
+        );
+    }
+
+    #[test]
+    fn eth_deposit_becomes_deposit_tx() {
+        let to = address!("00000000000000000000000000000000000000bb");
+        let mut body = to.to_vec();
+        body.extend_from_slice(&word(1_000));
+        let msg = message(L1MessageKind::EthDeposit, body);
+        let txs = arb_txs(&msg, 42161u64, None).unwrap();
+        assert_eq!(
+            txs,
+            alloc::vec![ArbTxEnvelope::Deposit(ArbDepositTx {
+                chain_id: U256::from(42161u64),
+                l1_request_id: msg.header.request_id.unwrap(),
+                from: msg.header.poster,
+                to,
+                value: U256::from(1_000u64),
+            })]
+        );
+    }
+
+    #[test]
+    fn submit_retryable_becomes_submit_tx() {
+        let mut body = Vec::new();
+        body.extend(addr_word(Address::ZERO));
+        body.extend_from_slice(&word(5));
+        body.extend_from_slice(&word(1_000_000));
+        body.extend_from_slice(&word(20_000));
+        body.extend(addr_word(Address::with_last_byte(1)));
+        body.extend(addr_word(Address::with_last_byte(2)));
+        body.extend_from_slice(&word(100_000));
+        body.extend_from_slice(&word(7));
+        body.extend_from_slice(&word(3));
+        body.extend_from_slice(&[0xde, 0xad, 0xbe]);
+        let msg = message(L1MessageKind::SubmitRetryable, body);
+        let txs = arb_txs(&msg, 42161u64, None).unwrap();
+        let ArbTxEnvelope::SubmitRetryable(tx) = &txs[0] else {
+            panic!("expected submit retryable");
+        };
+        assert_eq!(tx.retry_to, None);
+        assert_eq!(tx.retry_value, U256::from(5u64));
+        assert_eq!(tx.deposit_value, U256::from(1_000_000u64));
+        assert_eq!(tx.fee_refund_addr, Address::with_last_byte(1));
+        assert_eq!(tx.beneficiary, Address::with_last_byte(2));
+        assert_eq!(tx.gas, 100_000);
+        assert_eq!(tx.l1_base_fee, msg.header.l1_base_fee);
+        assert_eq!(tx.retry_data, Bytes::from_static(&[0xde, 0xad, 0xbe]));
+
+        let mut short = msg.clone();
+        short.l2_msg = msg.l2_msg.slice(..msg.l2_msg.len() - 1);
+        let txs = arb_txs(&short, 42161u64, None).unwrap();
+        let ArbTxEnvelope::SubmitRetryable(tx) = &txs[0] else {
+            panic!("expected submit retryable");
+        };
+        assert_eq!(tx.retry_data, Bytes::from_static(&[0xde, 0xad, 0]));
+
+        let mut empty = msg.clone();
+        empty.l2_msg = msg.l2_msg.slice(..msg.l2_msg.len() - 3);
+        assert_eq!(empty.decode_body(), Err(MessageError::Truncated));
+        let L1MessageBody::SubmitRetryable(fields) = message(
+            L1MessageKind::SubmitRetryable,
+            [&msg.l2_msg[..msg.l2_msg.len() - 35], &word(0)[..]].concat(),
+        )
+        .decode_body()
+        .unwrap() else {
+            panic!("expected submit retryable");
+        };
+        assert!(fields.data.is_empty());
+    }
+
+    #[test]
+    fn l2_funded_by_l1_yields_deposit_then_tx() {
+        let mut body = alloc::vec![L2_MESSAGE_KIND_CONTRACT_TX];
+        body.extend_from_slice(&word(21_000));
+        body.extend_from_slice(&word(100));
+        body.extend(addr_word(Address::with_last_byte(9)));
+        body.extend_from_slice(&word(77));
+        body.extend_from_slice(&[1, 2]);
+        let msg = message(L1MessageKind::L2FundedByL1, body);
+        let txs = arb_txs(&msg, 1, None).unwrap();
+        assert_eq!(txs.len(), 2);
+        let ArbTxEnvelope::Deposit(dep) = &txs[0] else {
+            panic!("expected deposit");
+        };
+        assert_eq!(dep.to, msg.header.poster);
+        assert_eq!(dep.value, U256::from(77u64));
+        assert_eq!(
+            dep.l1_request_id,
+            sub_request_id(msg.header.request_id.unwrap(), U256::ZERO)
+        );
+        let ArbTxEnvelope::Contract(c) = &txs[1] else {
+            panic!("expected contract tx");
+        };
+        assert_eq!(
+            c.request_id,
+            sub_request_id(msg.header.request_id.unwrap(), U256::from(1))
+        );
+        assert_eq!(c.to, Some(Address::with_last_byte(9)));
+        assert_eq!(c.data, Bytes::from_static(&[1, 2]));
+    }
+
+    #[test]
+    fn batch_posting_report_becomes_internal_tx() {
+        let mut body = Vec::new();
+        body.extend_from_slice(&word(1_700_000_000));
+        body.extend_from_slice(Address::with_last_byte(3).as_slice());
+        body.extend_from_slice(&[0x11; 32]);
+        body.extend_from_slice(&word(12));
+        body.extend_from_slice(&word(1_000));
+        let msg = message(L1MessageKind::BatchPostingReport, body.clone());
+        assert_eq!(
+            arb_txs(&msg, 1, None),
+            Err(MessageError::MissingBatchGasCost)
+        );
+        let txs = arb_txs(&msg, 1, Some(500)).unwrap();
+        let ArbTxEnvelope::Internal(itx) = &txs[0] else {
+            panic!("expected internal tx");
+        };
+        assert_eq!(itx.data.len(), 4 + 5 * 32);
+        assert_eq!(&itx.data[4 + 3 * 32..4 + 4 * 32], &word(500));
+
+        body.extend_from_slice(&25u64.to_be_bytes());
+        let msg = message(L1MessageKind::BatchPostingReport, body);
+        let L1MessageBody::BatchPostingReport(report) = msg.decode_body().unwrap() else {
+            panic!("expected report");
+        };
+        assert_eq!(report.extra_gas, 25);
+        assert_eq!(report.batch_number, 12);
+    }
+
+    #[test]
+    fn init_message_versions() {
+        let msg = message(L1MessageKind::Initialize, word(412346).to_vec());
+        let L1MessageBody::Initialize(init) = msg.decode_body().unwrap() else {
+            panic!("expected init");
+        };
+        assert_eq!(init.chain_id, U256::from(412346u64));
+        assert_eq!(
+            init.initial_l1_base_fee,
+            U256::from(DEFAULT_INITIAL_L1_BASE_FEE)
+        );
+
+        let mut body = word(412346).to_vec();
+        body.push(1);
+        body.extend_from_slice(&word(7));
+        body.extend_from_slice(b"{}");
+        let msg = message(L1MessageKind::Initialize, body);
+        let L1MessageBody::Initialize(init) = msg.decode_body().unwrap() else {
+            panic!("expected init");
+        };
+        assert_eq!(init.initial_l1_base_fee, U256::from(7u64));
+        assert_eq!(init.serialized_chain_config, Bytes::from_static(b"{}"));
+    }
+
+    #[test]
+    fn batch_gas_cost_matches_nitro_formula() {
+        let data = [0u8, 1, 0, 2];
+        let expected = 4 + 16 + 4 + 16 + 30 + 6 + 40_000;
+        assert_eq!(compute_batch_gas_cost(&data), expected);
+    }
+}
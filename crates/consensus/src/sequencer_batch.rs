@@ -0,0 +1,96 @@
+use alloy_primitives::{keccak256, B256};
+
+use crate::messages::MessageError;
+
+pub const SEQUENCER_BATCH_HEADER_LEN: usize = 40;
+
+pub const BROTLI_MESSAGE_HEADER_BYTE: u8 = 0x00;
+pub const DAS_MESSAGE_HEADER_FLAG: u8 = 0x80;
+pub const TREE_DAS_MESSAGE_HEADER_FLAG: u8 = 0x08;
+pub const L1_AUTHENTICATED_MESSAGE_HEADER_FLAG: u8 = 0x40;
+pub const ZEROHEAVY_MESSAGE_HEADER_FLAG: u8 = 0x20;
+pub const BLOB_HASHES_HEADER_FLAG: u8 = L1_AUTHENTICATED_MESSAGE_HEADER_FLAG | 0x10;
+
+/// The 40-byte header the sequencer inbox prepends to every batch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchHeader {
+    pub min_timestamp: u64,
+    pub max_timestamp: u64,
+    pub min_l1_block: u64,
+    pub max_l1_block: u64,
+    pub after_delayed_messages: u64,
+}
+
+impl BatchHeader {
+    /// Splits batch data into its header and the payload that follows it.
+    pub fn parse(data: &[u8]) -> Result<(Self, &[u8]), MessageError> {
+        if data.len() < SEQUENCER_BATCH_HEADER_LEN {
+            return Err(MessageError::Truncated);
+        }
+        let word = |i: usize| {
+            let mut b = [0u8; 8];
+            b.copy_from_slice(&data[i * 8..i * 8 + 8]);
+            u64::from_be_bytes(b)
+        };
+        let header = Self {
+            min_timestamp: word(0),
+            max_timestamp: word(1),
+            min_l1_block: word(2),
+            max_l1_block: word(3),
+            after_delayed_messages: word(4),
+        };
+        Ok((header, &data[SEQUENCER_BATCH_HEADER_LEN..]))
+    }
+
+    pub fn encode(&self) -> [u8; SEQUENCER_BATCH_HEADER_LEN] {
+        let mut out = [0u8; SEQUENCER_BATCH_HEADER_LEN];
+        for (i, v) in [
+            self.min_timestamp,
+            self.max_timestamp,
+            self.min_l1_block,
+            self.max_l1_block,
+            self.after_delayed_messages,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            out[i * 8..i * 8 + 8].copy_from_slice(&v.to_be_bytes());
+        }
+        out
+    }
+}
+
+/// The accumulator `Bridge.enqueueSequencerMessage` pushes for a batch.
+pub fn sequencer_batch_accumulator(before_acc: B256, data_hash: B256, delayed_acc: B256) -> B256 {
+    let mut packed = [0u8; 96];
+    packed[..32].copy_from_slice(before_acc.as_slice());
+    packed[32..64].copy_from_slice(data_hash.as_slice());
+    packed[64..].copy_from_slice(delayed_acc.as_slice());
+    keccak256(packed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_roundtrip() {
+        let header = BatchHeader {
+            min_timestamp: 1_000,
+            max_timestamp: 1_100,
+            min_l1_block: 50,
+            max_l1_block: 60,
+            after_delayed_messages: 3,
+        };
+        let mut data = header.encode().to_vec();
+        assert_eq!(data[..8], 1_000u64.to_be_bytes());
+        data.push(0xaa);
+        let (parsed, payload) = BatchHeader::parse(&data).unwrap();
+        assert_eq!(parsed, header);
+        assert_eq!(payload, &[0xaa]);
+        assert_eq!(
+            BatchHeader::parse(&data[..39]),
+            Err(MessageError::Truncated)
+        );
+    }
+}
@@ -0,0 +1,828 @@
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use arb_alloy_consensus::messages::{L1IncomingMessageHeader, MessageError};
+use arb_alloy_consensus::sequencer_batch::{
+    sequencer_batch_accumulator, BatchHeader, BLOB_HASHES_HEADER_FLAG,
+};
+use arb_alloy_consensus::{
+    ArbOsVersion, ArbTxEnvelope, L1IncomingMessage, L1MessageKind, L2MessageParser,
+};
+use thiserror::Error;
+
+use crate::{selector, topic};
+
+pub const SIG_INBOX_CREATE_RETRYABLE_TICKET: &str =
+    "createRetryableTicket(address,uint256,uint256,address,address,uint256,uint256,bytes)";
+pub const SIG_INBOX_UNSAFE_CREATE_RETRYABLE_TICKET: &str =
+    "unsafeCreateRetryableTicket(address,uint256,uint256,address,address,uint256,uint256,bytes)";
+pub const SIG_INBOX_DEPOSIT_ETH: &str = "depositEth()";
+pub const SIG_INBOX_SEND_L2_MESSAGE: &str = "sendL2Message(bytes)";
+pub const SIG_INBOX_SEND_CONTRACT_TRANSACTION: &str =
+    "sendContractTransaction(uint256,uint256,address,uint256,bytes)";
+
+pub const EVT_BRIDGE_MESSAGE_DELIVERED: &str =
+    "MessageDelivered(uint256,bytes32,address,uint8,address,bytes32,uint256,uint64)";
+pub const EVT_INBOX_MESSAGE_DELIVERED: &str = "InboxMessageDelivered(uint256,bytes)";
+pub const EVT_INBOX_MESSAGE_DELIVERED_FROM_ORIGIN: &str =
+    "InboxMessageDeliveredFromOrigin(uint256)";
+
+/// `AddressAliasHelper`'s offset for L1 contracts sending to L2.
+pub const L1_TO_L2_ALIAS_OFFSET: Address = Address::new([
+    0x11, 0x11, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x11, 0x11,
+]);
+
+pub fn apply_l1_to_l2_alias(l1_address: Address) -> Address {
+    let sum = U256::from_be_slice(l1_address.as_slice())
+        .wrapping_add(U256::from_be_slice(L1_TO_L2_ALIAS_OFFSET.as_slice()));
+    Address::from_word(sum.into())
+}
+
+pub fn undo_l1_to_l2_alias(l2_address: Address) -> Address {
+    let diff = U256::from_be_slice(l2_address.as_slice())
+        .wrapping_sub(U256::from_be_slice(L1_TO_L2_ALIAS_OFFSET.as_slice()));
+    Address::from_word(diff.into())
+}
+
+/// An argument of an ABI-encoded call made only of static words and `bytes`.
+pub(crate) enum Token<'a> {
+    Word(B256),
+    Bytes(&'a [u8]),
+}
+
+impl From<U256> for Token<'_> {
+    fn from(value: U256) -> Self {
+        Token::Word(value.into())
+    }
+}
+
+impl From<Address> for Token<'_> {
+    fn from(value: Address) -> Self {
+        Token::Word(value.into_word())
+    }
+}
+
+pub(crate) fn encode_call(sig: &str, tokens: &[Token<'_>]) -> Vec<u8> {
+    let mut head = Vec::with_capacity(4 + 32 * tokens.len());
+    let mut tail = Vec::new();
+    head.extend_from_slice(&selector(sig));
+    for token in tokens {
+        match token {
+            Token::Word(word) => head.extend_from_slice(word.as_slice()),
+            Token::Bytes(bytes) => {
+                let offset = 32 * tokens.len() + tail.len();
+                head.extend_from_slice(&U256::from(offset).to_be_bytes::<32>());
+                tail.extend_from_slice(&U256::from(bytes.len()).to_be_bytes::<32>());
+                tail.extend_from_slice(bytes);
+                tail.resize(tail.len().next_multiple_of(32), 0);
+            }
+        }
+    }
+    head.extend_from_slice(&tail);
+    head
+}
+
+/// Strips the selector of `sig` from `input`.
+pub(crate) fn call_args<'a>(input: &'a [u8], sig: &str) -> Result<&'a [u8], L1ContractError> {
+    match input.split_first_chunk::<4>() {
+        Some((sel, args)) if *sel == selector(sig) => Ok(args),
+        _ => Err(L1ContractError::SelectorMismatch),
+    }
+}
+
+pub(crate) fn abi_word(data: &[u8], index: usize) -> Result<U256, L1ContractError> {
+    index
+        .checked_mul(32)
+        .and_then(|start| data.get(start..start.checked_add(32)?))
+        .map(U256::from_be_slice)
+        .ok_or(L1ContractError::Truncated)
+}
+
+pub(crate) fn abi_u64(data: &[u8], index: usize) -> Result<u64, L1ContractError> {
+    u64::try_from(abi_word(data, index)?).map_err(|_| L1ContractError::ValueTooLarge)
+}
+
+pub(crate) fn abi_address(data: &[u8], index: usize) -> Result<Address, L1ContractError> {
+    Ok(Address::from_word(abi_word(data, index)?.into()))
+}
+
+/// The `bytes` argument whose offset sits in head slot `index`.
+pub(crate) fn abi_bytes(data: &[u8], index: usize) -> Result<&[u8], L1ContractError> {
+    let offset = usize::try_from(abi_word(data, index)?).map_err(|_| L1ContractError::Truncated)?;
+    let rest = data.get(offset..).ok_or(L1ContractError::Truncated)?;
+    let len = usize::try_from(abi_word(rest, 0)?).map_err(|_| L1ContractError::Truncated)?;
+    len.checked_add(32)
+        .and_then(|end| rest.get(32..end))
+        .ok_or(L1ContractError::Truncated)
+}
+
+/// Arguments shared by `createRetryableTicket` and
+/// `unsafeCreateRetryableTicket`. The deposit is the call's `msg.value`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetryableTicketParams {
+    pub to: Address,
+    pub l2_call_value: U256,
+    pub max_submission_cost: U256,
+    pub excess_fee_refund_address: Address,
+    pub call_value_refund_address: Address,
+    pub gas_limit: U256,
+    pub max_fee_per_gas: U256,
+    pub data: Bytes,
+}
+
+impl RetryableTicketParams {
+    fn tokens(&self) -> [Token<'_>; 8] {
+        [
+            self.to.into(),
+            self.l2_call_value.into(),
+            self.max_submission_cost.into(),
+            self.excess_fee_refund_address.into(),
+            self.call_value_refund_address.into(),
+            self.gas_limit.into(),
+            self.max_fee_per_gas.into(),
+            Token::Bytes(&self.data),
+        ]
+    }
+
+    /// The safe variant, which aliases refund addresses that are contracts
+    /// on L1 before delivering.
+    pub fn encode_create_retryable_ticket(&self) -> Vec<u8> {
+        encode_call(SIG_INBOX_CREATE_RETRYABLE_TICKET, &self.tokens())
+    }
+
+    pub fn encode_unsafe_create_retryable_ticket(&self) -> Vec<u8> {
+        encode_call(SIG_INBOX_UNSAFE_CREATE_RETRYABLE_TICKET, &self.tokens())
+    }
+
+    /// The message data the inbox delivers for these arguments, with refund
+    /// addresses already final.
+    pub fn message_data(&self, deposit: U256) -> Bytes {
+        let mut out = Vec::with_capacity(9 * 32 + self.data.len());
+        for word in [
+            self.to.into_word(),
+            self.l2_call_value.into(),
+            deposit.into(),
+            self.max_submission_cost.into(),
+            self.excess_fee_refund_address.into_word(),
+            self.call_value_refund_address.into_word(),
+            self.gas_limit.into(),
+            self.max_fee_per_gas.into(),
+            U256::from(self.data.len()).into(),
+        ] {
+            out.extend_from_slice(word.as_slice());
+        }
+        out.extend_from_slice(&self.data);
+        out.into()
+    }
+}
+
+pub fn encode_deposit_eth() -> Vec<u8> {
+    encode_call(SIG_INBOX_DEPOSIT_ETH, &[])
+}
+
+pub fn encode_send_l2_message(message: &[u8]) -> Vec<u8> {
+    encode_call(SIG_INBOX_SEND_L2_MESSAGE, &[Token::Bytes(message)])
+}
+
+pub fn encode_send_contract_transaction(
+    gas_limit: U256,
+    max_fee_per_gas: U256,
+    to: Address,
+    value: U256,
+    data: &[u8],
+) -> Vec<u8> {
+    encode_call(
+        SIG_INBOX_SEND_CONTRACT_TRANSACTION,
+        &[
+            gas_limit.into(),
+            max_fee_per_gas.into(),
+            to.into(),
+            value.into(),
+            Token::Bytes(data),
+        ],
+    )
+}
+
+/// The bridge's `MessageDelivered` log, emitted for every delayed message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageDelivered {
+    pub message_index: U256,
+    pub before_inbox_acc: B256,
+    pub inbox: Address,
+    pub kind: u8,
+    pub sender: Address,
+    pub message_data_hash: B256,
+    pub base_fee_l1: U256,
+    pub timestamp: u64,
+}
+
+impl MessageDelivered {
+    pub fn decode_log(topics: &[B256], data: &[u8]) -> Result<Self, L1ContractError> {
+        let [sig, message_index, before_inbox_acc] = topics else {
+            return Err(L1ContractError::UnexpectedLog);
+        };
+        if sig.0 != topic(EVT_BRIDGE_MESSAGE_DELIVERED) {
+            return Err(L1ContractError::UnexpectedLog);
+        }
+        let kind = u8::try_from(abi_word(data, 1)?).map_err(|_| L1ContractError::ValueTooLarge)?;
+        Ok(Self {
+            message_index: (*message_index).into(),
+            before_inbox_acc: *before_inbox_acc,
+            inbox: abi_address(data, 0)?,
+            kind,
+            sender: abi_address(data, 2)?,
+            message_data_hash: abi_word(data, 3)?.into(),
+            base_fee_l1: abi_word(data, 4)?,
+            timestamp: abi_u64(data, 5)?,
+        })
+    }
+
+    /// Pairs the log with the message data the inbox emitted for it, giving
+    /// the delayed message Nitro reads. `l1_block_number` is the block the
+    /// log was included in.
+    pub fn to_incoming_message(
+        &self,
+        l1_block_number: u64,
+        data: &[u8],
+    ) -> Result<L1IncomingMessage, L1ContractError> {
+        if keccak256(data) != self.message_data_hash {
+            return Err(L1ContractError::DataHashMismatch);
+        }
+        Ok(L1IncomingMessage {
+            header: L1IncomingMessageHeader {
+                kind: self.kind,
+                poster: self.sender,
+                block_number: l1_block_number,
+                timestamp: self.timestamp,
+                request_id: Some(self.message_index.into()),
+                l1_base_fee: self.base_fee_l1,
+            },
+            l2_msg: Bytes::copy_from_slice(data),
+        })
+    }
+}
+
+/// The inbox's `InboxMessageDelivered` log carrying a delayed message's data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InboxMessageDelivered {
+    pub message_num: U256,
+    pub data: Bytes,
+}
+
+impl InboxMessageDelivered {
+    pub fn decode_log(topics: &[B256], data: &[u8]) -> Result<Self, L1ContractError> {
+        let [sig, message_num] = topics else {
+            return Err(L1ContractError::UnexpectedLog);
+        };
+        if sig.0 != topic(EVT_INBOX_MESSAGE_DELIVERED) {
+            return Err(L1ContractError::UnexpectedLog);
+        }
+        Ok(Self {
+            message_num: (*message_num).into(),
+            data: Bytes::copy_from_slice(abi_bytes(data, 0)?),
+        })
+    }
+}
+
+/// Joins a bridge log with its inbox log into the delayed message.
+pub fn delivered_message(
+    delivered: &MessageDelivered,
+    inbox: &InboxMessageDelivered,
+    l1_block_number: u64,
+) -> Result<L1IncomingMessage, L1ContractError> {
+    if delivered.message_index != inbox.message_num {
+        return Err(L1ContractError::MessageNumMismatch {
+            delivered: delivered.message_index,
+            inbox: inbox.message_num,
+        });
+    }
+    delivered.to_incoming_message(l1_block_number, &inbox.data)
+}
+
+/// The `ArbSubmitRetryableTx` or `ArbDepositTx` a retryable or ETH deposit
+/// message turns into on L2.
+pub fn deposit_transaction(
+    msg: &L1IncomingMessage,
+    chain_id: U256,
+) -> Result<ArbTxEnvelope, L1ContractError> {
+    match msg.kind()? {
+        L1MessageKind::SubmitRetryable | L1MessageKind::EthDeposit => {}
+        _ => return Err(L1ContractError::NotADeposit(msg.header.kind)),
+    }
+    let parser = L2MessageParser::new(chain_id, ArbOsVersion::default());
+    msg.to_transactions(&parser, None)?
+        .pop()
+        .and_then(|tx| tx.into_arb())
+        .ok_or(L1ContractError::NotADeposit(msg.header.kind))
+}
+
+pub const SIG_SEQ_ADD_BATCH_FROM_ORIGIN: &str =
+    "addSequencerL2BatchFromOrigin(uint256,bytes,uint256,address,uint256,uint256)";
+pub const SIG_SEQ_ADD_BATCH_FROM_ORIGIN_LEGACY: &str =
+    "addSequencerL2BatchFromOrigin(uint256,bytes,uint256,address)";
+pub const SIG_SEQ_ADD_BATCH: &str =
+    "addSequencerL2Batch(uint256,bytes,uint256,address,uint256,uint256)";
+pub const SIG_SEQ_ADD_BATCH_FROM_BLOBS: &str =
+    "addSequencerL2BatchFromBlobs(uint256,uint256,address,uint256,uint256)";
+
+pub const EVT_SEQUENCER_BATCH_DELIVERED: &str = "SequencerBatchDelivered(uint256,bytes32,bytes32,bytes32,uint256,(uint64,uint64,uint64,uint64),uint8)";
+pub const EVT_SEQUENCER_BATCH_DATA: &str = "SequencerBatchData(uint256,bytes)";
+
+/// `IBridge.BatchDataLocation`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchDataLocation {
+    TxInput = 0,
+    SeparateBatchEvent = 1,
+    NoData = 2,
+    Blob = 3,
+}
+
+impl BatchDataLocation {
+    pub fn from_u8(b: u8) -> Result<Self, L1ContractError> {
+        match b {
+            0 => Ok(Self::TxInput),
+            1 => Ok(Self::SeparateBatchEvent),
+            2 => Ok(Self::NoData),
+            3 => Ok(Self::Blob),
+            _ => Err(L1ContractError::UnknownDataLocation(b)),
+        }
+    }
+}
+
+/// `IBridge.TimeBounds`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeBounds {
+    pub min_timestamp: u64,
+    pub max_timestamp: u64,
+    pub min_block_number: u64,
+    pub max_block_number: u64,
+}
+
+/// The sequencer inbox's `SequencerBatchDelivered` log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequencerBatchDelivered {
+    pub batch_sequence_number: u64,
+    pub before_acc: B256,
+    pub after_acc: B256,
+    pub delayed_acc: B256,
+    pub after_delayed_messages_read: u64,
+    pub time_bounds: TimeBounds,
+    pub data_location: BatchDataLocation,
+}
+
+impl SequencerBatchDelivered {
+    pub fn decode_log(topics: &[B256], data: &[u8]) -> Result<Self, L1ContractError> {
+        let [sig, batch_sequence_number, before_acc, after_acc] = topics else {
+            return Err(L1ContractError::UnexpectedLog);
+        };
+        if sig.0 != topic(EVT_SEQUENCER_BATCH_DELIVERED) {
+            return Err(L1ContractError::UnexpectedLog);
+        }
+        let location =
+            u8::try_from(abi_word(data, 6)?).map_err(|_| L1ContractError::ValueTooLarge)?;
+        Ok(Self {
+            batch_sequence_number: u64::try_from(U256::from_be_bytes(batch_sequence_number.0))
+                .map_err(|_| L1ContractError::ValueTooLarge)?,
+            before_acc: *before_acc,
+            after_acc: *after_acc,
+            delayed_acc: abi_word(data, 0)?.into(),
+            after_delayed_messages_read: abi_u64(data, 1)?,
+            time_bounds: TimeBounds {
+                min_timestamp: abi_u64(data, 2)?,
+                max_timestamp: abi_u64(data, 3)?,
+                min_block_number: abi_u64(data, 4)?,
+                max_block_number: abi_u64(data, 5)?,
+            },
+            data_location: BatchDataLocation::from_u8(location)?,
+        })
+    }
+
+    /// The header the inbox prepends to the batch data.
+    pub fn header(&self) -> BatchHeader {
+        let bounds = self.time_bounds;
+        BatchHeader {
+            min_timestamp: bounds.min_timestamp,
+            max_timestamp: bounds.max_timestamp,
+            min_l1_block: bounds.min_block_number,
+            max_l1_block: bounds.max_block_number,
+            after_delayed_messages: self.after_delayed_messages_read,
+        }
+    }
+
+    /// The serialized batch the decoder reads. `data` is the calldata
+    /// argument, the `SequencerBatchData` payload, or the batch's versioned
+    /// hashes from [`blob_batch_data`], depending on the data location.
+    pub fn sequencer_message(&self, data: &[u8]) -> Result<Vec<u8>, L1ContractError> {
+        if self.data_location == BatchDataLocation::NoData && !data.is_empty() {
+            return Err(L1ContractError::UnexpectedBatchData);
+        }
+        let header = self.header().encode();
+        let mut out = Vec::with_capacity(header.len() + data.len());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(data);
+        Ok(out)
+    }
+
+    /// Checks `after_acc` against the accumulator the bridge would push for
+    /// `sequencer_message`.
+    pub fn verify_accumulator(&self, sequencer_message: &[u8]) -> Result<(), L1ContractError> {
+        let acc = sequencer_batch_accumulator(
+            self.before_acc,
+            keccak256(sequencer_message),
+            self.delayed_acc,
+        );
+        if acc != self.after_acc {
+            return Err(L1ContractError::AccumulatorMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// The batch data of a blob batch: the header flag and the versioned hashes
+/// of the transaction's blobs.
+pub fn blob_batch_data(versioned_hashes: &[B256]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 + 32 * versioned_hashes.len());
+    out.push(BLOB_HASHES_HEADER_FLAG);
+    for hash in versioned_hashes {
+        out.extend_from_slice(hash.as_slice());
+    }
+    out
+}
+
+/// The `SequencerBatchData` log of a batch delivered from a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequencerBatchData {
+    pub batch_sequence_number: U256,
+    pub data: Bytes,
+}
+
+impl SequencerBatchData {
+    pub fn decode_log(topics: &[B256], data: &[u8]) -> Result<Self, L1ContractError> {
+        let [sig, batch_sequence_number] = topics else {
+            return Err(L1ContractError::UnexpectedLog);
+        };
+        if sig.0 != topic(EVT_SEQUENCER_BATCH_DATA) {
+            return Err(L1ContractError::UnexpectedLog);
+        }
+        Ok(Self {
+            batch_sequence_number: (*batch_sequence_number).into(),
+            data: Bytes::copy_from_slice(abi_bytes(data, 0)?),
+        })
+    }
+}
+
+/// A decoded call that posts a batch to the sequencer inbox. Blob batches
+/// carry no data; it comes from the transaction's blob hashes instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddSequencerBatchCall {
+    pub sequence_number: u64,
+    pub data: Bytes,
+    pub after_delayed_messages_read: u64,
+    pub gas_refunder: Address,
+    /// Absent from the legacy `addSequencerL2BatchFromOrigin`.
+    pub prev_message_count: Option<U256>,
+    pub new_message_count: Option<U256>,
+}
+
+impl AddSequencerBatchCall {
+    pub fn decode(input: &[u8]) -> Result<Self, L1ContractError> {
+        for sig in [SIG_SEQ_ADD_BATCH_FROM_ORIGIN, SIG_SEQ_ADD_BATCH] {
+            if let Ok(args) = call_args(input, sig) {
+                return Self::decode_with_data(args, true);
+            }
+        }
+        if let Ok(args) = call_args(input, SIG_SEQ_ADD_BATCH_FROM_ORIGIN_LEGACY) {
+            return Self::decode_with_data(args, false);
+        }
+        let args = call_args(input, SIG_SEQ_ADD_BATCH_FROM_BLOBS)?;
+        Ok(Self {
+            sequence_number: abi_u64(args, 0)?,
+            data: Bytes::new(),
+            after_delayed_messages_read: abi_u64(args, 1)?,
+            gas_refunder: abi_address(args, 2)?,
+            prev_message_count: Some(abi_word(args, 3)?),
+            new_message_count: Some(abi_word(args, 4)?),
+        })
+    }
+
+    fn decode_with_data(args: &[u8], message_counts: bool) -> Result<Self, L1ContractError> {
+        let (prev_message_count, new_message_count) = if message_counts {
+            (Some(abi_word(args, 4)?), Some(abi_word(args, 5)?))
+        } else {
+            (None, None)
+        };
+        Ok(Self {
+            sequence_number: abi_u64(args, 0)?,
+            data: Bytes::copy_from_slice(abi_bytes(args, 1)?),
+            after_delayed_messages_read: abi_u64(args, 2)?,
+            gas_refunder: abi_address(args, 3)?,
+            prev_message_count,
+            new_message_count,
+        })
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum L1ContractError {
+    #[error("calldata does not start with the expected selector")]
+    SelectorMismatch,
+    #[error("ABI data is truncated")]
+    Truncated,
+    #[error("ABI value does not fit its type")]
+    ValueTooLarge,
+    #[error("log does not match the expected event")]
+    UnexpectedLog,
+    #[error("message data does not match the delivered hash")]
+    DataHashMismatch,
+    #[error("bridge delivered message {delivered} but inbox emitted {inbox}")]
+    MessageNumMismatch { delivered: U256, inbox: U256 },
+    #[error("message kind {0} is not a deposit")]
+    NotADeposit(u8),
+    #[error("unknown batch data location {0}")]
+    UnknownDataLocation(u8),
+    #[error("batch without data location carries data")]
+    UnexpectedBatchData,
+    #[error("batch does not lead to the logged accumulator")]
+    AccumulatorMismatch,
+    #[error(transparent)]
+    Message(#[from] MessageError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use arb_alloy_consensus::tx::ArbDepositTx;
+
+    fn bridge_log(kind: L1MessageKind, sender: Address, data: &[u8]) -> (Vec<B256>, Vec<u8>) {
+        let topics = alloc::vec![
+            B256::from(topic(EVT_BRIDGE_MESSAGE_DELIVERED)),
+            B256::from(U256::from(42)),
+            B256::repeat_byte(0xac),
+        ];
+        let mut body = Vec::new();
+        for word in [
+            Address::with_last_byte(0x1b).into_word(),
+            U256::from(kind.as_u8()).into(),
+            sender.into_word(),
+            keccak256(data),
+            U256::from(30_000_000_000u64).into(),
+            U256::from(1_700_000_000u64).into(),
+        ] {
+            body.extend_from_slice(word.as_slice());
+        }
+        (topics, body)
+    }
+
+    fn inbox_log(data: &[u8]) -> InboxMessageDelivered {
+        let topics = [
+            B256::from(topic(EVT_INBOX_MESSAGE_DELIVERED)),
+            B256::from(U256::from(42)),
+        ];
+        let encoded = encode_send_l2_message(data);
+        InboxMessageDelivered::decode_log(&topics, &encoded[4..]).unwrap()
+    }
+
+    #[test]
+    fn aliasing_roundtrips_and_wraps() {
+        let l1 = address!("ffffffffffffffffffffffffffffffffffffffff");
+        let l2 = apply_l1_to_l2_alias(l1);
+        assert_eq!(l2, address!("1111000000000000000000000000000000001110"));
+        assert_eq!(undo_l1_to_l2_alias(l2), l1);
+    }
+
+    #[test]
+    fn encodes_calls_with_dynamic_bytes() {
+        assert_eq!(
+            encode_deposit_eth(),
+            selector(SIG_INBOX_DEPOSIT_ETH).to_vec()
+        );
+        let call = encode_send_contract_transaction(
+            U256::from(100_000),
+            U256::from(1),
+            Address::with_last_byte(9),
+            U256::ZERO,
+            &[0xab; 33],
+        );
+        let args = call_args(&call, SIG_INBOX_SEND_CONTRACT_TRANSACTION).unwrap();
+        assert_eq!(args.len(), 5 * 32 + 32 + 64);
+        assert_eq!(abi_word(args, 4).unwrap(), U256::from(5 * 32));
+        assert_eq!(abi_address(args, 2).unwrap(), Address::with_last_byte(9));
+        assert_eq!(abi_bytes(args, 4).unwrap(), &[0xab; 33]);
+        assert_eq!(
+            call_args(&call, SIG_INBOX_SEND_L2_MESSAGE),
+            Err(L1ContractError::SelectorMismatch)
+        );
+
+        let params = RetryableTicketParams {
+            data: Bytes::from_static(&[1, 2, 3]),
+            ..Default::default()
+        };
+        let safe = params.encode_create_retryable_ticket();
+        let unsafe_call = params.encode_unsafe_create_retryable_ticket();
+        assert_eq!(safe[4..], unsafe_call[4..]);
+        assert_ne!(safe[..4], unsafe_call[..4]);
+    }
+
+    #[test]
+    fn reconstructs_submit_retryable_from_logs() {
+        let sender = apply_l1_to_l2_alias(Address::with_last_byte(0x55));
+        let params = RetryableTicketParams {
+            to: Address::with_last_byte(0x10),
+            l2_call_value: U256::from(5),
+            max_submission_cost: U256::from(1_000),
+            excess_fee_refund_address: Address::with_last_byte(0x20),
+            call_value_refund_address: Address::with_last_byte(0x30),
+            gas_limit: U256::from(200_000),
+            max_fee_per_gas: U256::from(100_000_000),
+            data: Bytes::from_static(&[0xca, 0xfe]),
+        };
+        let data = params.message_data(U256::from(10_000));
+        let (topics, body) = bridge_log(L1MessageKind::SubmitRetryable, sender, &data);
+        let delivered = MessageDelivered::decode_log(&topics, &body).unwrap();
+        assert_eq!(delivered.timestamp, 1_700_000_000);
+        let msg = delivered_message(&delivered, &inbox_log(&data), 18_000_000).unwrap();
+        assert_eq!(msg.header.request_id, Some(B256::from(U256::from(42))));
+
+        let ArbTxEnvelope::SubmitRetryable(tx) =
+            deposit_transaction(&msg, U256::from(42161)).unwrap()
+        else {
+            panic!("expected a submit retryable tx");
+        };
+        assert_eq!(tx.from, sender);
+        assert_eq!(tx.deposit_value, U256::from(10_000));
+        assert_eq!(tx.retry_to, Some(params.to));
+        assert_eq!(tx.beneficiary, params.call_value_refund_address);
+        assert_eq!(tx.fee_refund_addr, params.excess_fee_refund_address);
+        assert_eq!(tx.gas, 200_000);
+        assert_eq!(tx.l1_base_fee, U256::from(30_000_000_000u64));
+        assert_eq!(tx.retry_data, params.data);
+    }
+
+    #[test]
+    fn reconstructs_eth_deposit_and_rejects_mismatches() {
+        let sender = Address::with_last_byte(0x77);
+        let mut data = Vec::from(sender.as_slice());
+        data.extend_from_slice(&U256::from(1_000_000).to_be_bytes::<32>());
+        let (topics, body) = bridge_log(L1MessageKind::EthDeposit, sender, &data);
+        let delivered = MessageDelivered::decode_log(&topics, &body).unwrap();
+        let msg = delivered.to_incoming_message(1, &data).unwrap();
+        assert_eq!(
+            deposit_transaction(&msg, U256::from(1)).unwrap(),
+            ArbTxEnvelope::Deposit(ArbDepositTx {
+                chain_id: U256::from(1),
+                l1_request_id: B256::from(U256::from(42)),
+                from: sender,
+                to: sender,
+                value: U256::from(1_000_000),
+            })
+        );
+
+        assert_eq!(
+            delivered.to_incoming_message(1, &data[1..]),
+            Err(L1ContractError::DataHashMismatch)
+        );
+        let mut other = inbox_log(&data);
+        other.message_num = U256::from(43);
+        assert!(matches!(
+            delivered_message(&delivered, &other, 1),
+            Err(L1ContractError::MessageNumMismatch { .. })
+        ));
+        assert_eq!(
+            MessageDelivered::decode_log(&topics[..2], &body),
+            Err(L1ContractError::UnexpectedLog)
+        );
+
+        let (topics, body) = bridge_log(L1MessageKind::L2Message, sender, &data);
+        let msg = MessageDelivered::decode_log(&topics, &body)
+            .unwrap()
+            .to_incoming_message(1, &data)
+            .unwrap();
+        assert_eq!(
+            deposit_transaction(&msg, U256::from(1)),
+            Err(L1ContractError::NotADeposit(3))
+        );
+    }
+
+    fn batch_log(location: BatchDataLocation, message: &[u8]) -> (Vec<B256>, Vec<u8>) {
+        let before_acc = B256::repeat_byte(0x01);
+        let delayed_acc = B256::repeat_byte(0x02);
+        let mut packed = Vec::from(before_acc.as_slice());
+        packed.extend_from_slice(keccak256(message).as_slice());
+        packed.extend_from_slice(delayed_acc.as_slice());
+        let topics = alloc::vec![
+            B256::from(topic(EVT_SEQUENCER_BATCH_DELIVERED)),
+            B256::from(U256::from(7)),
+            before_acc,
+            keccak256(packed),
+        ];
+        let mut data = Vec::from(delayed_acc.as_slice());
+        for v in [3u64, 100, 200, 10, 20, location as u64] {
+            data.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        }
+        (topics, data)
+    }
+
+    #[test]
+    fn decodes_batch_delivered_and_rebuilds_message() {
+        let payload = [0x00, 0xaa, 0xbb];
+        let mut expected = Vec::new();
+        for v in [100u64, 200, 10, 20, 3] {
+            expected.extend_from_slice(&v.to_be_bytes());
+        }
+        expected.extend_from_slice(&payload);
+
+        let (topics, data) = batch_log(BatchDataLocation::TxInput, &expected);
+        let log = SequencerBatchDelivered::decode_log(&topics, &data).unwrap();
+        assert_eq!(log.batch_sequence_number, 7);
+        assert_eq!(log.after_delayed_messages_read, 3);
+        assert_eq!(log.time_bounds.max_block_number, 20);
+        let message = log.sequencer_message(&payload).unwrap();
+        assert_eq!(message, expected);
+        log.verify_accumulator(&message).unwrap();
+        assert_eq!(
+            log.verify_accumulator(&message[1..]),
+            Err(L1ContractError::AccumulatorMismatch)
+        );
+
+        let hashes = [B256::repeat_byte(0x01), B256::repeat_byte(0x02)];
+        let blob_data = blob_batch_data(&hashes);
+        assert_eq!(blob_data.len(), 65);
+        assert_eq!(blob_data[0], BLOB_HASHES_HEADER_FLAG);
+
+        let (topics, mut data) = batch_log(BatchDataLocation::NoData, &expected);
+        let log = SequencerBatchDelivered::decode_log(&topics, &data).unwrap();
+        assert_eq!(
+            log.sequencer_message(&payload),
+            Err(L1ContractError::UnexpectedBatchData)
+        );
+        data[6 * 32 + 31] = 4;
+        assert_eq!(
+            SequencerBatchDelivered::decode_log(&topics, &data),
+            Err(L1ContractError::UnknownDataLocation(4))
+        );
+    }
+
+    #[test]
+    fn decodes_batch_posting_calls() {
+        let refunder = Address::with_last_byte(0xfe);
+        let payload = [0x00u8; 50];
+        let call = encode_call(
+            SIG_SEQ_ADD_BATCH_FROM_ORIGIN,
+            &[
+                U256::from(9).into(),
+                Token::Bytes(&payload),
+                U256::from(4).into(),
+                refunder.into(),
+                U256::from(100).into(),
+                U256::from(120).into(),
+            ],
+        );
+        let decoded = AddSequencerBatchCall::decode(&call).unwrap();
+        assert_eq!(decoded.sequence_number, 9);
+        assert_eq!(decoded.data, Bytes::copy_from_slice(&payload));
+        assert_eq!(decoded.after_delayed_messages_read, 4);
+        assert_eq!(decoded.gas_refunder, refunder);
+        assert_eq!(decoded.new_message_count, Some(U256::from(120)));
+
+        let legacy = encode_call(
+            SIG_SEQ_ADD_BATCH_FROM_ORIGIN_LEGACY,
+            &[
+                U256::from(9).into(),
+                Token::Bytes(&payload),
+                U256::from(4).into(),
+                refunder.into(),
+            ],
+        );
+        let decoded = AddSequencerBatchCall::decode(&legacy).unwrap();
+        assert_eq!(decoded.prev_message_count, None);
+        assert_eq!(decoded.data.len(), 50);
+
+        let blobs = encode_call(
+            SIG_SEQ_ADD_BATCH_FROM_BLOBS,
+            &[
+                U256::from(10).into(),
+                U256::from(5).into(),
+                refunder.into(),
+                U256::from(120).into(),
+                U256::from(130).into(),
+            ],
+        );
+        let decoded = AddSequencerBatchCall::decode(&blobs).unwrap();
+        assert_eq!(decoded.sequence_number, 10);
+        assert!(decoded.data.is_empty());
+        assert_eq!(decoded.prev_message_count, Some(U256::from(120)));
+
+        assert_eq!(
+            AddSequencerBatchCall::decode(&encode_deposit_eth()),
+            Err(L1ContractError::SelectorMismatch)
+        );
+
+        let topics = [
+            B256::from(topic(EVT_SEQUENCER_BATCH_DATA)),
+            B256::from(U256::from(9)),
+        ];
+        let event = encode_send_l2_message(&payload);
+        let data = SequencerBatchData::decode_log(&topics, &event[4..]).unwrap();
+        assert_eq!(data.data.len(), 50);
+    }
+}
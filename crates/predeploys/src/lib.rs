@@ -3,8 +3,11 @@
 
 extern crate alloc;
 
+pub mod l1_contracts;
+
 use alloc::vec::Vec;
 use alloy_primitives::keccak256;
+use arb_alloy_consensus::ArbOsVersion;
 
 pub const ARB_SYS: [u8; 20] = hex20(0x64);
 pub const ARB_ADDRESS_TABLE: [u8; 20] = hex20(0x66);
@@ -22,6 +25,8 @@ pub const ARB_NATIVE_TOKEN_MANAGER: [u8; 20] = hex20(0x73);
 pub const NODE_INTERFACE: [u8; 20] = hex20(0xc8);
 pub const NODE_INTERFACE_DEBUG: [u8; 20] = hex20(0xc9);
 pub const ARB_DEBUG: [u8; 20] = hex20(0xff);
+pub const ARBOS_TEST: [u8; 20] = hex20(0x69);
+pub const ARB_AGGREGATOR: [u8; 20] = hex20(0x6d);
 
 /* ArbSys core */
 pub const SIG_WITHDRAW_ETH: &str = "withdrawEth(address)";
@@ -164,11 +169,120 @@ pub const EVT_REDEEMED_DEPRECATED: &str = "Redeemed(bytes32)";
 
 /* ArbSys events */
 pub const EVT_L2_TO_L1_TX: &str =
-    "L2ToL1Tx(address,address,uint256,uint256,uint256,uint256,uint256,bytes)";
+    "L2ToL1Tx(address,address,uint256,uint256,uint256,uint256,uint256,uint256,bytes)";
 pub const EVT_L2_TO_L1_TRANSACTION_DEPRECATED: &str =
     "L2ToL1Transaction(address,address,uint256,uint256,uint256,uint256,uint256,uint256,uint256,bytes)";
 pub const EVT_SEND_MERKLE_UPDATE: &str = "SendMerkleUpdate(uint256,bytes32,uint256)";
 
+/* ArbGasInfo, version gated */
+pub const SIG_GI_GET_L1_FEES_AVAILABLE: &str = "getL1FeesAvailable()";
+pub const SIG_GI_GET_L1_PRICING_EQUILIBRATION_UNITS: &str = "getL1PricingEquilibrationUnits()";
+
+const PRECOMPILE_MIN_VERSIONS: &[([u8; 20], ArbOsVersion)] = &[
+    (ARB_SYS, ArbOsVersion(0)),
+    (ARB_INFO, ArbOsVersion(0)),
+    (ARB_ADDRESS_TABLE, ArbOsVersion(0)),
+    (ARB_FUNCTION_TABLE, ArbOsVersion(0)),
+    (ARBOS_TEST, ArbOsVersion(0)),
+    (ARB_OWNER_PUBLIC, ArbOsVersion(0)),
+    (ARB_GAS_INFO, ArbOsVersion(0)),
+    (ARB_AGGREGATOR, ArbOsVersion(0)),
+    (ARB_RETRYABLE_TX, ArbOsVersion(0)),
+    (ARB_STATISTICS, ArbOsVersion(0)),
+    (ARB_OWNER, ArbOsVersion(0)),
+    (ARB_WASM, ArbOsVersion::STYLUS),
+    (ARB_WASM_CACHE, ArbOsVersion::STYLUS),
+    (ARB_NATIVE_TOKEN_MANAGER, ArbOsVersion::V41),
+    (NODE_INTERFACE, ArbOsVersion(0)),
+    (NODE_INTERFACE_DEBUG, ArbOsVersion(0)),
+    (ARB_DEBUG, ArbOsVersion(0)),
+];
+
+const METHOD_MIN_VERSIONS: &[([u8; 20], &str, ArbOsVersion)] = &[
+    (
+        ARB_GAS_INFO,
+        SIG_GI_GET_L1_FEES_AVAILABLE,
+        ArbOsVersion::V10,
+    ),
+    (ARB_GAS_INFO, SIG_GI_GET_L1_REWARD_RATE, ArbOsVersion::V11),
+    (
+        ARB_GAS_INFO,
+        SIG_GI_GET_L1_REWARD_RECIPIENT,
+        ArbOsVersion::V11,
+    ),
+    (
+        ARB_GAS_INFO,
+        SIG_GI_GET_L1_PRICING_EQUILIBRATION_UNITS,
+        ArbOsVersion::V20,
+    ),
+    (ARB_OWNER, SIG_OWNER_GET_INFRA_FEE_ACCOUNT, ArbOsVersion::V5),
+    (ARB_OWNER, SIG_OWNER_SET_INFRA_FEE_ACCOUNT, ArbOsVersion::V5),
+    (
+        ARB_OWNER_PUBLIC,
+        SIG_OWNER_PUB_GET_INFRA_FEE_ACCOUNT,
+        ArbOsVersion::V5,
+    ),
+    (
+        ARB_OWNER_PUBLIC,
+        SIG_OWNER_PUB_RECTIFY_CHAIN_OWNER,
+        ArbOsVersion::V11,
+    ),
+    (
+        ARB_OWNER_PUBLIC,
+        SIG_OWNER_PUB_GET_BROTLI_COMPRESSION_LEVEL,
+        ArbOsVersion::V20,
+    ),
+    (
+        ARB_OWNER_PUBLIC,
+        SIG_OWNER_PUB_GET_SCHEDULED_UPGRADE,
+        ArbOsVersion::V20,
+    ),
+    (
+        ARB_OWNER_PUBLIC,
+        SIG_OWNER_PUB_IS_CALLDATA_PRICE_INCREASE_ENABLED,
+        ArbOsVersion::V40,
+    ),
+    (
+        ARB_OWNER_PUBLIC,
+        SIG_OWNER_PUB_IS_NATIVE_TOKEN_OWNER,
+        ArbOsVersion::V41,
+    ),
+    (
+        ARB_OWNER_PUBLIC,
+        SIG_OWNER_PUB_GET_ALL_NATIVE_TOKEN_OWNERS,
+        ArbOsVersion::V41,
+    ),
+];
+
+/// The ArbOS version a precompile appeared in, or `None` for an address that
+/// isn't a known precompile.
+pub fn precompile_min_arbos_version(address: [u8; 20]) -> Option<ArbOsVersion> {
+    PRECOMPILE_MIN_VERSIONS
+        .iter()
+        .find(|(addr, _)| *addr == address)
+        .map(|(_, version)| *version)
+}
+
+pub fn is_precompile_available(address: [u8; 20], version: ArbOsVersion) -> bool {
+    precompile_min_arbos_version(address).is_some_and(|min| version.is_at_least(min))
+}
+
+/// The ArbOS version a precompile method became callable in. Methods without
+/// their own gate inherit the precompile's.
+pub fn method_min_arbos_version(address: [u8; 20], sig: &str) -> Option<ArbOsVersion> {
+    let precompile = precompile_min_arbos_version(address)?;
+    let method = METHOD_MIN_VERSIONS
+        .iter()
+        .find(|(addr, s, _)| *addr == address && *s == sig)
+        .map(|(_, _, version)| *version)
+        .unwrap_or_default();
+    Some(precompile.max(method))
+}
+
+pub fn is_method_available(address: [u8; 20], sig: &str, version: ArbOsVersion) -> bool {
+    method_min_arbos_version(address, sig).is_some_and(|min| version.is_at_least(min))
+}
+
 pub fn signature_bytes(sig: &str) -> Vec<u8> {
     sig.as_bytes().to_vec()
 }
@@ -252,6 +366,50 @@ mod tests {
         }
     }
 
+    #[test]
+    fn precompile_availability_follows_arbos_version() {
+        assert!(is_precompile_available(ARB_SYS, ArbOsVersion::V1));
+        assert!(!is_precompile_available(ARB_WASM, ArbOsVersion::V20));
+        assert!(is_precompile_available(ARB_WASM, ArbOsVersion::STYLUS));
+        assert!(!is_precompile_available(
+            ARB_NATIVE_TOKEN_MANAGER,
+            ArbOsVersion::V40
+        ));
+        assert_eq!(precompile_min_arbos_version(hex20(0x01)), None);
+        assert!(!is_precompile_available(hex20(0x01), ArbOsVersion::V50));
+    }
+
+    #[test]
+    fn method_availability_follows_arbos_version() {
+        let sched = SIG_OWNER_PUB_GET_SCHEDULED_UPGRADE;
+        assert!(!is_method_available(
+            ARB_OWNER_PUBLIC,
+            sched,
+            ArbOsVersion::V11
+        ));
+        assert!(is_method_available(
+            ARB_OWNER_PUBLIC,
+            sched,
+            ArbOsVersion::ATLAS
+        ));
+        assert_eq!(
+            method_min_arbos_version(
+                ARB_OWNER_PUBLIC,
+                SIG_OWNER_PUB_IS_CALLDATA_PRICE_INCREASE_ENABLED
+            ),
+            Some(ArbOsVersion::V40)
+        );
+        assert_eq!(
+            method_min_arbos_version(ARB_WASM, SIG_WASM_INK_PRICE),
+            Some(ArbOsVersion::STYLUS)
+        );
+        assert!(is_method_available(
+            ARB_SYS,
+            SIG_ARB_BLOCK_NUMBER,
+            ArbOsVersion::V1
+        ));
+    }
+
     #[test]
     fn owner_selectors_compile() {
         for sig in [
@@ -0,0 +1,322 @@
+#![allow(dead_code)]
+
+use alloy_primitives::{address, keccak256, Address, B256};
+
+/// The account whose storage holds all of ArbOS's state.
+pub const ARBOS_STATE_ADDRESS: Address = address!("a4b05fffffffffffffffffffffffffffffffffff");
+
+pub const ARBOS_VERSION_OFFSET: u64 = 0;
+pub const UPGRADE_VERSION_OFFSET: u64 = 1;
+pub const UPGRADE_TIMESTAMP_OFFSET: u64 = 2;
+pub const NETWORK_FEE_ACCOUNT_OFFSET: u64 = 3;
+pub const CHAIN_ID_OFFSET: u64 = 4;
+pub const GENESIS_BLOCK_NUM_OFFSET: u64 = 5;
+pub const INFRA_FEE_ACCOUNT_OFFSET: u64 = 6;
+pub const BROTLI_COMPRESSION_LEVEL_OFFSET: u64 = 7;
+pub const NATIVE_TOKEN_ENABLED_FROM_TIME_OFFSET: u64 = 8;
+
+pub const L1_PRICING_SUBSPACE: u8 = 0;
+pub const L2_PRICING_SUBSPACE: u8 = 1;
+pub const RETRYABLES_SUBSPACE: u8 = 2;
+pub const ADDRESS_TABLE_SUBSPACE: u8 = 3;
+pub const CHAIN_OWNER_SUBSPACE: u8 = 4;
+pub const SEND_MERKLE_SUBSPACE: u8 = 5;
+pub const BLOCKHASHES_SUBSPACE: u8 = 6;
+pub const CHAIN_CONFIG_SUBSPACE: u8 = 7;
+pub const PROGRAMS_SUBSPACE: u8 = 8;
+pub const FEATURES_SUBSPACE: u8 = 9;
+pub const NATIVE_TOKEN_OWNER_SUBSPACE: u8 = 10;
+
+pub const L1_PAY_REWARDS_TO_OFFSET: u64 = 0;
+pub const L1_EQUILIBRATION_UNITS_OFFSET: u64 = 1;
+pub const L1_INERTIA_OFFSET: u64 = 2;
+pub const L1_PER_UNIT_REWARD_OFFSET: u64 = 3;
+pub const L1_LAST_UPDATE_TIME_OFFSET: u64 = 4;
+pub const L1_FUNDS_DUE_FOR_REWARDS_OFFSET: u64 = 5;
+pub const L1_UNITS_SINCE_OFFSET: u64 = 6;
+pub const L1_PRICE_PER_UNIT_OFFSET: u64 = 7;
+pub const L1_LAST_SURPLUS_OFFSET: u64 = 8;
+pub const L1_PER_BATCH_GAS_COST_OFFSET: u64 = 9;
+pub const L1_AMORTIZED_COST_CAP_BIPS_OFFSET: u64 = 10;
+pub const L1_FEES_AVAILABLE_OFFSET: u64 = 11;
+pub const L1_GAS_FLOOR_PER_TOKEN_OFFSET: u64 = 12;
+pub const L1_BATCH_POSTER_TABLE_KEY: u8 = 0;
+pub const L1_POSTER_ADDRS_KEY: u8 = 0;
+pub const L1_POSTER_INFO_KEY: u8 = 1;
+pub const L1_POSTER_FUNDS_DUE_OFFSET: u64 = 0;
+pub const L1_POSTER_PAY_TO_OFFSET: u64 = 1;
+
+pub const L2_SPEED_LIMIT_PER_SECOND_OFFSET: u64 = 0;
+pub const L2_PER_BLOCK_GAS_LIMIT_OFFSET: u64 = 1;
+pub const L2_BASE_FEE_WEI_OFFSET: u64 = 2;
+pub const L2_MIN_BASE_FEE_WEI_OFFSET: u64 = 3;
+pub const L2_GAS_BACKLOG_OFFSET: u64 = 4;
+pub const L2_PRICING_INERTIA_OFFSET: u64 = 5;
+pub const L2_BACKLOG_TOLERANCE_OFFSET: u64 = 6;
+pub const L2_PER_TX_GAS_LIMIT_OFFSET: u64 = 7;
+
+pub const RETRYABLE_TIMEOUT_QUEUE_KEY: u8 = 0;
+pub const RETRYABLE_CALLDATA_KEY: u8 = 1;
+pub const RETRYABLE_NUM_TRIES_OFFSET: u64 = 0;
+pub const RETRYABLE_FROM_OFFSET: u64 = 1;
+pub const RETRYABLE_TO_OFFSET: u64 = 2;
+pub const RETRYABLE_CALLVALUE_OFFSET: u64 = 3;
+pub const RETRYABLE_BENEFICIARY_OFFSET: u64 = 4;
+pub const RETRYABLE_TIMEOUT_OFFSET: u64 = 5;
+pub const RETRYABLE_TIMEOUT_WINDOWS_LEFT_OFFSET: u64 = 6;
+
+pub const PROGRAMS_PARAMS_KEY: u8 = 0;
+pub const PROGRAMS_DATA_KEY: u8 = 1;
+pub const PROGRAMS_MODULE_HASHES_KEY: u8 = 2;
+pub const PROGRAMS_DATA_PRICER_KEY: u8 = 3;
+pub const PROGRAMS_CACHE_MANAGERS_KEY: u8 = 4;
+
+pub const DATA_PRICER_DEMAND_OFFSET: u64 = 0;
+pub const DATA_PRICER_BYTES_PER_SECOND_OFFSET: u64 = 1;
+pub const DATA_PRICER_LAST_UPDATE_TIME_OFFSET: u64 = 2;
+pub const DATA_PRICER_MIN_PRICE_OFFSET: u64 = 3;
+pub const DATA_PRICER_INERTIA_OFFSET: u64 = 4;
+
+/// A node of ArbOS's storage tree, as Nitro's `storage.Storage` addresses it.
+/// The root has an empty key and each subspace's key is the keccak of its
+/// parent's key and its id.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArbosStorage {
+    key: Option<B256>,
+}
+
+impl ArbosStorage {
+    pub const fn root() -> Self {
+        Self { key: None }
+    }
+
+    fn key_bytes(&self) -> &[u8] {
+        self.key.as_ref().map_or(&[], |key| key.as_slice())
+    }
+
+    /// Nitro's `OpenSubStorage`.
+    pub fn subspace(&self, id: &[u8]) -> Self {
+        let mut preimage = self.key_bytes().to_vec();
+        preimage.extend_from_slice(id);
+        Self {
+            key: Some(keccak256(preimage)),
+        }
+    }
+
+    /// Nitro's `mapAddress`: hashes all but the last byte of `key` under this
+    /// storage's key and keeps the last byte, so consecutive offsets share a
+    /// 256-slot page.
+    pub fn slot(&self, key: B256) -> B256 {
+        let mut preimage = self.key_bytes().to_vec();
+        preimage.extend_from_slice(&key[..31]);
+        let mut slot = keccak256(preimage);
+        slot[31] = key[31];
+        slot
+    }
+
+    pub fn offset_slot(&self, offset: u64) -> B256 {
+        let mut key = B256::ZERO;
+        key[24..].copy_from_slice(&offset.to_be_bytes());
+        self.slot(key)
+    }
+
+    /// The slot of a mapping entry keyed by `address`.
+    pub fn address_slot(&self, address: Address) -> B256 {
+        self.slot(address.into_word())
+    }
+}
+
+pub fn arbos_state_slot(offset: u64) -> B256 {
+    ArbosStorage::root().offset_slot(offset)
+}
+
+pub fn arbos_version_slot() -> B256 {
+    arbos_state_slot(ARBOS_VERSION_OFFSET)
+}
+
+fn root_subspace(id: u8) -> ArbosStorage {
+    ArbosStorage::root().subspace(&[id])
+}
+
+pub fn l1_pricing_storage() -> ArbosStorage {
+    root_subspace(L1_PRICING_SUBSPACE)
+}
+
+pub fn l2_pricing_storage() -> ArbosStorage {
+    root_subspace(L2_PRICING_SUBSPACE)
+}
+
+pub fn retryables_storage() -> ArbosStorage {
+    root_subspace(RETRYABLES_SUBSPACE)
+}
+
+pub fn address_table_storage() -> ArbosStorage {
+    root_subspace(ADDRESS_TABLE_SUBSPACE)
+}
+
+pub fn chain_owners_storage() -> ArbosStorage {
+    root_subspace(CHAIN_OWNER_SUBSPACE)
+}
+
+pub fn programs_storage() -> ArbosStorage {
+    root_subspace(PROGRAMS_SUBSPACE)
+}
+
+/// Where `L1PricingState::l1_base_fee_wei` lives.
+pub fn l1_price_per_unit_slot() -> B256 {
+    l1_pricing_storage().offset_slot(L1_PRICE_PER_UNIT_OFFSET)
+}
+
+/// A batch poster's entry under the poster table's info subspace; its
+/// offsets are the funds due and the pay-to address.
+pub fn batch_poster_storage(poster: Address) -> ArbosStorage {
+    l1_pricing_storage()
+        .subspace(&[L1_BATCH_POSTER_TABLE_KEY])
+        .subspace(&[L1_POSTER_INFO_KEY])
+        .subspace(poster.as_slice())
+}
+
+pub fn batch_poster_funds_due_slot(poster: Address) -> B256 {
+    batch_poster_storage(poster).offset_slot(L1_POSTER_FUNDS_DUE_OFFSET)
+}
+
+pub fn l2_base_fee_slot() -> B256 {
+    l2_pricing_storage().offset_slot(L2_BASE_FEE_WEI_OFFSET)
+}
+
+pub fn retryable_storage(ticket_id: B256) -> ArbosStorage {
+    retryables_storage().subspace(ticket_id.as_slice())
+}
+
+pub fn retryable_slot(ticket_id: B256, offset: u64) -> B256 {
+    retryable_storage(ticket_id).offset_slot(offset)
+}
+
+/// Holds the ticket's expiry timestamp, zero once redeemed or reaped.
+pub fn retryable_timeout_slot(ticket_id: B256) -> B256 {
+    retryable_slot(ticket_id, RETRYABLE_TIMEOUT_OFFSET)
+}
+
+/// The ticket's calldata as Nitro's `StorageBackedBytes`: the length at
+/// offset 0 and 32-byte words from offset 1.
+pub fn retryable_calldata_storage(ticket_id: B256) -> ArbosStorage {
+    retryable_storage(ticket_id).subspace(&[RETRYABLE_CALLDATA_KEY])
+}
+
+/// The address table's size lives at offset 0 and entry `i` (zero-based) at
+/// offset `i + 1`.
+pub fn address_table_entry_slot(index: u64) -> B256 {
+    address_table_storage().offset_slot(index.saturating_add(1))
+}
+
+/// Holds the address's one-based index in the table, or zero if absent.
+pub fn address_table_index_slot(address: Address) -> B256 {
+    address_table_storage().subspace(&[]).address_slot(address)
+}
+
+/// Nonzero iff `owner` is a chain owner.
+pub fn chain_owner_slot(owner: Address) -> B256 {
+    chain_owners_storage().subspace(&[0]).address_slot(owner)
+}
+
+/// The packed `StylusParams` word.
+pub fn stylus_params_slot() -> B256 {
+    programs_storage()
+        .subspace(&[PROGRAMS_PARAMS_KEY])
+        .offset_slot(0)
+}
+
+/// The packed `StylusProgram` word for a code hash.
+pub fn stylus_program_slot(code_hash: B256) -> B256 {
+    programs_storage()
+        .subspace(&[PROGRAMS_DATA_KEY])
+        .slot(code_hash)
+}
+
+pub fn stylus_module_hash_slot(code_hash: B256) -> B256 {
+    programs_storage()
+        .subspace(&[PROGRAMS_MODULE_HASHES_KEY])
+        .slot(code_hash)
+}
+
+pub fn data_pricer_slot(offset: u64) -> B256 {
+    programs_storage()
+        .subspace(&[PROGRAMS_DATA_PRICER_KEY])
+        .offset_slot(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l1_pricing::BATCH_POSTER_ADDRESS;
+    use alloy_primitives::b256;
+
+    #[test]
+    fn root_fields_map_into_one_page() {
+        assert_eq!(
+            arbos_version_slot(),
+            b256!("15fed0451499512d95f3ec5a41c878b9de55f21878b5b4e190d4667ec709b400")
+        );
+        let chain_id = arbos_state_slot(CHAIN_ID_OFFSET);
+        assert_eq!(chain_id[..31], arbos_version_slot()[..31]);
+        assert_eq!(chain_id[31], 4);
+    }
+
+    #[test]
+    fn subspaces_chain_keccak_keys() {
+        let l1 = l1_pricing_storage();
+        assert_eq!(l1, ArbosStorage::root().subspace(&[0]));
+        assert_eq!(l1.key, Some(keccak256([0u8])));
+
+        let mut expected = keccak256([keccak256([0u8]).as_slice(), &[0u8; 31]].concat());
+        expected[31] = 7;
+        assert_eq!(l1_price_per_unit_slot(), expected);
+
+        let ticket = B256::repeat_byte(0x11);
+        let retryable = retryables_storage().subspace(ticket.as_slice());
+        assert_eq!(
+            retryable.key,
+            Some(keccak256(
+                [keccak256([2u8]).as_slice(), ticket.as_slice()].concat()
+            ))
+        );
+        assert_eq!(
+            retryable_timeout_slot(ticket),
+            retryable.offset_slot(RETRYABLE_TIMEOUT_OFFSET)
+        );
+        assert_ne!(
+            retryable_timeout_slot(ticket),
+            retryable_timeout_slot(B256::ZERO)
+        );
+    }
+
+    #[test]
+    fn batch_poster_entries_sit_under_the_info_subspace() {
+        let table = keccak256([keccak256([0u8]).as_slice(), &[0]].concat());
+        let info = keccak256([table.as_slice(), &[1]].concat());
+        let poster = keccak256([info.as_slice(), BATCH_POSTER_ADDRESS.as_slice()].concat());
+        assert_eq!(batch_poster_storage(BATCH_POSTER_ADDRESS).key, Some(poster));
+        assert_eq!(
+            batch_poster_funds_due_slot(BATCH_POSTER_ADDRESS),
+            b256!("26438022d7bc3dacb366c03344760cb0b1045ad014826fa28bcc2ae55cc91600")
+        );
+    }
+
+    #[test]
+    fn mapping_entries_keep_the_key_low_byte() {
+        let owner = Address::with_last_byte(0xab);
+        let slot = chain_owner_slot(owner);
+        assert_eq!(slot[31], 0xab);
+        assert_eq!(
+            slot,
+            chain_owners_storage()
+                .subspace(&[0])
+                .slot(owner.into_word())
+        );
+        assert_ne!(address_table_index_slot(owner), slot);
+        assert_eq!(
+            address_table_entry_slot(0),
+            address_table_storage().offset_slot(1)
+        );
+    }
+}
@@ -0,0 +1,235 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use alloy_primitives::B256;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::sequencer_batch::{BatchError, BatchHeader, BatchPayloadKind};
+
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+pub const BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT;
+pub const BYTES_PER_COMMITMENT: usize = 48;
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// Nitro leaves the first byte of every field element zero so the element
+/// stays below the BLS modulus.
+pub const USABLE_BYTES_PER_FIELD_ELEMENT: usize = BYTES_PER_FIELD_ELEMENT - 1;
+pub const USABLE_BYTES_PER_BLOB: usize = FIELD_ELEMENTS_PER_BLOB * USABLE_BYTES_PER_FIELD_ELEMENT;
+
+/// The EIP-4844 versioned hash of a KZG commitment.
+pub fn kzg_to_versioned_hash(commitment: &[u8; BYTES_PER_COMMITMENT]) -> B256 {
+    let mut hash: [u8; 32] = Sha256::digest(commitment).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    B256::from(hash)
+}
+
+/// Checks that every versioned hash commits to the commitment at the same
+/// index.
+pub fn verify_versioned_hashes(
+    versioned_hashes: &[B256],
+    commitments: &[[u8; BYTES_PER_COMMITMENT]],
+) -> Result<(), BlobError> {
+    if versioned_hashes.len() != commitments.len() {
+        return Err(BlobError::CountMismatch {
+            hashes: versioned_hashes.len(),
+            other: commitments.len(),
+        });
+    }
+    for (index, (hash, commitment)) in versioned_hashes.iter().zip(commitments).enumerate() {
+        if kzg_to_versioned_hash(commitment) != *hash {
+            return Err(BlobError::VersionedHashMismatch(index));
+        }
+    }
+    Ok(())
+}
+
+/// Nitro's `EncodeBlobs`: RLP-wraps `data` and packs it 31 bytes per field
+/// element.
+pub fn encode_blobs(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut rlp = Vec::new();
+    alloy_rlp::Encodable::encode(data, &mut rlp);
+    rlp.chunks(USABLE_BYTES_PER_BLOB)
+        .map(|chunk| {
+            let mut blob = alloc::vec![0u8; BYTES_PER_BLOB];
+            for (i, piece) in chunk.chunks(USABLE_BYTES_PER_FIELD_ELEMENT).enumerate() {
+                let start = i * BYTES_PER_FIELD_ELEMENT + 1;
+                blob[start..start + piece.len()].copy_from_slice(piece);
+            }
+            blob
+        })
+        .collect()
+}
+
+/// Nitro's `DecodeBlobs`: concatenates the usable bytes of every field
+/// element and decodes the RLP byte string they hold.
+pub fn decode_blobs<B: AsRef<[u8]>>(blobs: &[B]) -> Result<Vec<u8>, BlobError> {
+    let mut rlp = Vec::with_capacity(blobs.len() * USABLE_BYTES_PER_BLOB);
+    for (index, blob) in blobs.iter().enumerate() {
+        let blob = blob.as_ref();
+        if blob.len() != BYTES_PER_BLOB {
+            return Err(BlobError::InvalidBlobLength {
+                index,
+                len: blob.len(),
+            });
+        }
+        for element in blob.chunks_exact(BYTES_PER_FIELD_ELEMENT) {
+            rlp.extend_from_slice(&element[1..]);
+        }
+    }
+    let data = alloy_rlp::Header::decode_bytes(&mut &rlp[..], false)
+        .map_err(|_| BlobError::InvalidEncoding)?;
+    Ok(data.to_vec())
+}
+
+/// The versioned hashes listed after a blob batch's header flag byte.
+pub fn parse_blob_hashes(payload: &[u8]) -> Result<Vec<B256>, BlobError> {
+    match BatchPayloadKind::classify(payload)? {
+        BatchPayloadKind::Blob => {}
+        _ => return Err(BlobError::NotBlobBatch),
+    }
+    let hashes = &payload[1..];
+    if !hashes.len().is_multiple_of(32) {
+        return Err(BlobError::InvalidHashList(hashes.len()));
+    }
+    Ok(hashes.chunks_exact(32).map(B256::from_slice).collect())
+}
+
+/// Recovers the payload of a blob batch from its blobs and their
+/// commitments, in the order the batch lists the versioned hashes. The
+/// result goes to `decode_resolved_batch` along with the header.
+///
+/// The versioned hashes only authenticate the commitments. `verify_blob` is
+/// called with each blob's index, contents and commitment and must check
+/// the blob against the commitment, e.g. with the blob's KZG proof.
+pub fn resolve_blob_batch<B, F>(
+    data: &[u8],
+    commitments: &[[u8; BYTES_PER_COMMITMENT]],
+    blobs: &[B],
+    mut verify_blob: F,
+) -> Result<(BatchHeader, Vec<u8>), BlobError>
+where
+    B: AsRef<[u8]>,
+    F: FnMut(usize, &[u8], &[u8; BYTES_PER_COMMITMENT]) -> bool,
+{
+    let (header, payload) = BatchHeader::parse(data).map_err(|_| BatchError::MissingHeader)?;
+    let hashes = parse_blob_hashes(payload)?;
+    if hashes.len() != blobs.len() {
+        return Err(BlobError::CountMismatch {
+            hashes: hashes.len(),
+            other: blobs.len(),
+        });
+    }
+    verify_versioned_hashes(&hashes, commitments)?;
+    for (index, (blob, commitment)) in blobs.iter().zip(commitments).enumerate() {
+        if !verify_blob(index, blob.as_ref(), commitment) {
+            return Err(BlobError::BlobCommitmentMismatch(index));
+        }
+    }
+    Ok((header, decode_blobs(blobs)?))
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum BlobError {
+    #[error("blob {index} has length {len}")]
+    InvalidBlobLength { index: usize, len: usize },
+    #[error("{hashes} versioned hashes but {other} blobs or commitments")]
+    CountMismatch { hashes: usize, other: usize },
+    #[error("versioned hash {0} does not match its commitment")]
+    VersionedHashMismatch(usize),
+    #[error("blob {0} does not match its commitment")]
+    BlobCommitmentMismatch(usize),
+    #[error("blob hash list length {0} is not a multiple of 32")]
+    InvalidHashList(usize),
+    #[error("batch is not blob-sourced")]
+    NotBlobBatch,
+    #[error("blobs do not hold an RLP byte string")]
+    InvalidEncoding,
+    #[error(transparent)]
+    Batch(#[from] BatchError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer_batch::BLOB_HASHES_HEADER_FLAG;
+
+    #[test]
+    fn versioned_hash_of_zero_commitment() {
+        // sha256 of 48 zero bytes with the version byte swapped in.
+        let hash = kzg_to_versioned_hash(&[0u8; BYTES_PER_COMMITMENT]);
+        assert_eq!(
+            hash,
+            "0x01b0761f87b081d5cf10757ccc89f12be355c70e2e29df288b65b30710dcbcd1"
+                .parse::<B256>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn roundtrips_across_blobs() {
+        let data: Vec<u8> = (0..USABLE_BYTES_PER_BLOB + 100)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let blobs = encode_blobs(&data);
+        assert_eq!(blobs.len(), 2);
+        assert!(blobs
+            .iter()
+            .flat_map(|b| b.chunks_exact(BYTES_PER_FIELD_ELEMENT))
+            .all(|fe| fe[0] == 0));
+        assert_eq!(decode_blobs(&blobs).unwrap(), data);
+        assert_eq!(
+            decode_blobs(&[alloc::vec![0u8; 10]]),
+            Err(BlobError::InvalidBlobLength { index: 0, len: 10 })
+        );
+    }
+
+    #[test]
+    fn resolves_batch_and_checks_commitments() {
+        let commitments = [[1u8; BYTES_PER_COMMITMENT], [2u8; BYTES_PER_COMMITMENT]];
+        let payload: Vec<u8> = (0..USABLE_BYTES_PER_BLOB).map(|i| i as u8).collect();
+        let blobs = encode_blobs(&payload);
+        assert_eq!(blobs.len(), 2);
+
+        let header = BatchHeader {
+            after_delayed_messages: 4,
+            ..Default::default()
+        };
+        let mut data = header.encode().to_vec();
+        data.push(BLOB_HASHES_HEADER_FLAG);
+        for c in &commitments {
+            data.extend_from_slice(kzg_to_versioned_hash(c).as_slice());
+        }
+        // Stands in for a KZG check: commitment `i` is `[i + 1; 48]`.
+        let verify = |index: usize, _: &[u8], commitment: &[u8; BYTES_PER_COMMITMENT]| {
+            commitment[0] as usize == index + 1
+        };
+        let (parsed, resolved) = resolve_blob_batch(&data, &commitments, &blobs, verify).unwrap();
+        assert_eq!(parsed, header);
+        assert_eq!(resolved, payload);
+
+        let swapped = [commitments[1], commitments[0]];
+        assert_eq!(
+            resolve_blob_batch(&data, &swapped, &blobs, verify),
+            Err(BlobError::VersionedHashMismatch(0))
+        );
+        assert_eq!(
+            resolve_blob_batch(&data, &commitments, &blobs, |index, _, _| index != 1),
+            Err(BlobError::BlobCommitmentMismatch(1))
+        );
+        assert_eq!(
+            resolve_blob_batch(&data, &commitments, &blobs[..1], verify),
+            Err(BlobError::CountMismatch {
+                hashes: 2,
+                other: 1
+            })
+        );
+        data.pop();
+        assert_eq!(
+            parse_blob_hashes(&data[40..]),
+            Err(BlobError::InvalidHashList(63))
+        );
+        assert_eq!(parse_blob_hashes(&[0x00]), Err(BlobError::NotBlobBatch));
+    }
+}
@@ -0,0 +1,328 @@
+#![allow(dead_code)]
+
+use alloc::string::String;
+#[cfg(feature = "serde")]
+use alloc::vec::Vec;
+use alloy_primitives::{address, Address};
+use arb_alloy_consensus::ArbOsVersion;
+
+pub const ARBITRUM_ONE_CHAIN_ID: u64 = 42161;
+pub const ARBITRUM_NOVA_CHAIN_ID: u64 = 42170;
+pub const ARBITRUM_SEPOLIA_CHAIN_ID: u64 = 421614;
+
+/// Parent chain contracts of a rollup, as listed under `rollup` in Nitro's
+/// chain info.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RollupAddresses {
+    pub bridge: Address,
+    pub inbox: Address,
+    pub sequencer_inbox: Address,
+    pub rollup: Address,
+    /// ERC-20 used as the chain's gas token, `None` for ETH.
+    pub native_token: Option<Address>,
+    pub deployed_at: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbitrumChainParams {
+    pub chain_name: String,
+    pub chain_id: u64,
+    pub parent_chain_id: u64,
+    /// First L2 block produced by Nitro.
+    pub genesis_block_num: u64,
+    /// Parent chain block the rollup was deployed at.
+    pub genesis_l1_block: u64,
+    pub initial_arbos_version: ArbOsVersion,
+    pub initial_chain_owner: Address,
+    /// Set for AnyTrust chains, which post DAS certificates instead of data.
+    pub data_availability_committee: bool,
+    pub rollup: RollupAddresses,
+}
+
+impl ArbitrumChainParams {
+    pub fn arbitrum_one() -> Self {
+        Self {
+            chain_name: String::from("arb1"),
+            chain_id: ARBITRUM_ONE_CHAIN_ID,
+            parent_chain_id: 1,
+            genesis_block_num: 22_207_817,
+            genesis_l1_block: 15_411_056,
+            initial_arbos_version: ArbOsVersion::V6,
+            initial_chain_owner: address!("d345e41ae2cb00311956aa7109fc801ae8c81a52"),
+            data_availability_committee: false,
+            rollup: RollupAddresses {
+                bridge: address!("8315177ab297ba92a06054ce80a67ed4dbd7ed3a"),
+                inbox: address!("4dbd4fc535ac27206064b68ffcf827b0a60bab3f"),
+                sequencer_inbox: address!("1c479675ad559dc151f6ec7ed3fbf8cee79582b6"),
+                rollup: address!("5ef0d09d1e6204141b4d37530808ed19f60fba35"),
+                native_token: None,
+                deployed_at: 15_411_056,
+            },
+        }
+    }
+
+    pub fn arbitrum_nova() -> Self {
+        Self {
+            chain_name: String::from("nova"),
+            chain_id: ARBITRUM_NOVA_CHAIN_ID,
+            parent_chain_id: 1,
+            genesis_block_num: 0,
+            genesis_l1_block: 15_016_829,
+            initial_arbos_version: ArbOsVersion::V1,
+            initial_chain_owner: address!("9c040726f2a657226ed95712245dee84b650a1b5"),
+            data_availability_committee: true,
+            rollup: RollupAddresses {
+                bridge: address!("c1ebd02f738644983b6c4b2d440b8e77dde276bd"),
+                inbox: address!("c4448b71118c9071bcb9734a0eac55d18a153949"),
+                sequencer_inbox: address!("211e1c4c7f1bf5351ac850ed10fd68cffcf6c21b"),
+                rollup: address!("fb209827c58283535b744575e11953dcc4bead88"),
+                native_token: None,
+                deployed_at: 15_016_829,
+            },
+        }
+    }
+
+    pub fn arbitrum_sepolia() -> Self {
+        Self {
+            chain_name: String::from("sepolia-rollup"),
+            chain_id: ARBITRUM_SEPOLIA_CHAIN_ID,
+            parent_chain_id: 11_155_111,
+            genesis_block_num: 0,
+            genesis_l1_block: 4_139_226,
+            initial_arbos_version: ArbOsVersion::V10,
+            initial_chain_owner: address!("71b61c2e250afa05dfc36304d6c91501be0965d8"),
+            data_availability_committee: false,
+            rollup: RollupAddresses {
+                bridge: address!("38f918d0e9f1b721edaa41302e399fa1b79333a9"),
+                inbox: address!("aae29b0366299461418f5324a79afc425be5ae21"),
+                sequencer_inbox: address!("6c97864ce4bef387de0b3310a44230f7e3f1be0d"),
+                rollup: address!("d80810638dbdf9081b72c1b33c65375e807281c8"),
+                native_token: None,
+                deployed_at: 4_139_226,
+            },
+        }
+    }
+
+    pub fn by_chain_id(chain_id: u64) -> Option<Self> {
+        match chain_id {
+            ARBITRUM_ONE_CHAIN_ID => Some(Self::arbitrum_one()),
+            ARBITRUM_NOVA_CHAIN_ID => Some(Self::arbitrum_nova()),
+            ARBITRUM_SEPOLIA_CHAIN_ID => Some(Self::arbitrum_sepolia()),
+            _ => None,
+        }
+    }
+
+    pub fn is_anytrust(&self) -> bool {
+        self.data_availability_committee
+    }
+
+    pub fn custom_gas_token(&self) -> Option<Address> {
+        self.rollup.native_token
+    }
+}
+
+#[cfg(feature = "serde")]
+mod chain_info {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub(super) struct ChainInfo {
+        #[serde(default)]
+        pub chain_name: String,
+        #[serde(default)]
+        pub parent_chain_id: u64,
+        pub chain_config: ChainConfig,
+        #[serde(default)]
+        pub rollup: Rollup,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub(super) struct ChainConfig {
+        pub chain_id: u64,
+        pub arbitrum: ArbitrumConfig,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    pub(super) struct ArbitrumConfig {
+        #[serde(default)]
+        pub data_availability_committee: bool,
+        #[serde(rename = "InitialArbOSVersion")]
+        pub initial_arbos_version: u64,
+        #[serde(default)]
+        pub initial_chain_owner: Address,
+        #[serde(default)]
+        pub genesis_block_num: u64,
+    }
+
+    #[derive(Default, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub(super) struct Rollup {
+        #[serde(default)]
+        pub bridge: Address,
+        #[serde(default)]
+        pub inbox: Address,
+        #[serde(default)]
+        pub sequencer_inbox: Address,
+        #[serde(default)]
+        pub rollup: Address,
+        #[serde(default)]
+        pub native_token: Address,
+        #[serde(default)]
+        pub deployed_at: u64,
+    }
+
+    impl From<ChainInfo> for ArbitrumChainParams {
+        fn from(info: ChainInfo) -> Self {
+            let arb = info.chain_config.arbitrum;
+            let native_token =
+                Some(info.rollup.native_token).filter(|token| *token != Address::ZERO);
+            Self {
+                chain_name: info.chain_name,
+                chain_id: info.chain_config.chain_id,
+                parent_chain_id: info.parent_chain_id,
+                genesis_block_num: arb.genesis_block_num,
+                genesis_l1_block: info.rollup.deployed_at,
+                initial_arbos_version: ArbOsVersion(arb.initial_arbos_version),
+                initial_chain_owner: arb.initial_chain_owner,
+                data_availability_committee: arb.data_availability_committee,
+                rollup: RollupAddresses {
+                    bridge: info.rollup.bridge,
+                    inbox: info.rollup.inbox,
+                    sequencer_inbox: info.rollup.sequencer_inbox,
+                    rollup: info.rollup.rollup,
+                    native_token,
+                    deployed_at: info.rollup.deployed_at,
+                },
+            }
+        }
+    }
+}
+
+/// Parses Nitro's `chain-info` JSON, either the usual array of chains or a
+/// single chain object.
+#[cfg(feature = "serde")]
+pub fn parse_chain_info(json: &str) -> Result<Vec<ArbitrumChainParams>, ChainInfoError> {
+    let invalid = |err: serde_json::Error| {
+        ChainInfoError::InvalidJson(alloc::string::ToString::to_string(&err))
+    };
+    // Picking the shape up front keeps serde's error pointing at the field.
+    if json.trim_start().starts_with('[') {
+        let chains: Vec<chain_info::ChainInfo> = serde_json::from_str(json).map_err(invalid)?;
+        Ok(chains.into_iter().map(Into::into).collect())
+    } else {
+        let chain: chain_info::ChainInfo = serde_json::from_str(json).map_err(invalid)?;
+        Ok(alloc::vec![chain.into()])
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum ChainInfoError {
+    #[error("invalid chain info JSON: {0}")]
+    InvalidJson(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_found_by_chain_id() {
+        for chain_id in [
+            ARBITRUM_ONE_CHAIN_ID,
+            ARBITRUM_NOVA_CHAIN_ID,
+            ARBITRUM_SEPOLIA_CHAIN_ID,
+        ] {
+            let params = ArbitrumChainParams::by_chain_id(chain_id).unwrap();
+            assert_eq!(params.chain_id, chain_id);
+            assert_eq!(params.custom_gas_token(), None);
+        }
+        assert!(ArbitrumChainParams::by_chain_id(1).is_none());
+    }
+
+    #[test]
+    fn nova_is_the_only_anytrust_preset() {
+        assert!(ArbitrumChainParams::arbitrum_nova().is_anytrust());
+        assert!(!ArbitrumChainParams::arbitrum_one().is_anytrust());
+        assert!(!ArbitrumChainParams::arbitrum_sepolia().is_anytrust());
+        assert_eq!(
+            ArbitrumChainParams::arbitrum_one().genesis_block_num,
+            22_207_817
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parses_orbit_chain_info() {
+        let json = r#"[{
+            "chain-name": "my-orbit",
+            "parent-chain-id": 42161,
+            "parent-chain-is-arbitrum": true,
+            "has-genesis-state": false,
+            "chain-config": {
+                "chainId": 123456,
+                "homesteadBlock": 0,
+                "arbitrum": {
+                    "EnableArbOS": true,
+                    "AllowDebugPrecompiles": false,
+                    "DataAvailabilityCommittee": true,
+                    "InitialArbOSVersion": 32,
+                    "InitialChainOwner": "0x0000000000000000000000000000000000000abc",
+                    "GenesisBlockNum": 0,
+                    "MaxCodeSize": 24576
+                }
+            },
+            "rollup": {
+                "bridge": "0x0000000000000000000000000000000000000001",
+                "inbox": "0x0000000000000000000000000000000000000002",
+                "sequencer-inbox": "0x0000000000000000000000000000000000000003",
+                "rollup": "0x0000000000000000000000000000000000000004",
+                "native-token": "0x0000000000000000000000000000000000000005",
+                "deployed-at": 1234
+            }
+        }]"#;
+        let chains = parse_chain_info(json).unwrap();
+        assert_eq!(chains.len(), 1);
+        let c = &chains[0];
+        assert_eq!(c.chain_name, "my-orbit");
+        assert_eq!(c.chain_id, 123456);
+        assert_eq!(c.parent_chain_id, 42161);
+        assert_eq!(c.initial_arbos_version, ArbOsVersion::V32);
+        assert!(c.is_anytrust());
+        assert_eq!(c.genesis_l1_block, 1234);
+        assert_eq!(c.rollup.sequencer_inbox, Address::with_last_byte(3));
+        assert_eq!(c.custom_gas_token(), Some(Address::with_last_byte(5)));
+        assert_eq!(
+            c.initial_chain_owner,
+            address!("0000000000000000000000000000000000000abc")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parses_single_object_and_rejects_garbage() {
+        let json = r#"{
+            "chain-config": {
+                "chainId": 7,
+                "arbitrum": { "InitialArbOSVersion": 20 }
+            }
+        }"#;
+        let chains = parse_chain_info(json).unwrap();
+        assert_eq!(chains[0].chain_id, 7);
+        assert_eq!(chains[0].custom_gas_token(), None);
+        assert!(matches!(
+            parse_chain_info("{"),
+            Err(ChainInfoError::InvalidJson(_))
+        ));
+        let bad_field = r#"[{ "chain-config": { "chainId": "seven" } }]"#;
+        let Err(ChainInfoError::InvalidJson(err)) = parse_chain_info(bad_field) else {
+            panic!("expected a JSON error");
+        };
+        assert!(err.contains("line 1 column"), "{err}");
+        assert!(err.contains("invalid type"), "{err}");
+    }
+}
@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+
+/// The window size, `lgwin`, Nitro compresses with.
+pub const BROTLI_WINDOW_SIZE: i32 = 22;
+pub const BROTLI_MAX_LEVEL: u32 = 11;
+
+/// Brotli-compresses `data` at `level` with Nitro's window size.
+pub fn brotli_compress(data: &[u8], level: u32) -> Vec<u8> {
+    let params = brotli::enc::BrotliEncoderParams {
+        quality: level as i32,
+        lgwin: BROTLI_WINDOW_SIZE,
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    brotli::BrotliCompress(&mut &data[..], &mut out, &params)
+        .expect("compressing into a Vec cannot fail");
+    out
+}
+
+/// Length of `data` after brotli compression at `level` with Nitro's window size.
+pub fn brotli_compressed_len(data: &[u8], level: u32) -> u64 {
+    brotli_compress(data, level).len() as u64
+}
+
+/// Brotli-decompresses `data`, failing if the output exceeds `max_len`.
+pub fn brotli_decompress(data: &[u8], max_len: usize) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    brotli::Decompressor::new(data, 4096)
+        .take(max_len as u64 + 1)
+        .read_to_end(&mut out)
+        .ok()?;
+    (out.len() <= max_len).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrips_within_the_limit() {
+        let data = [7u8; 1_000];
+        let compressed = brotli_compress(&data, BROTLI_MAX_LEVEL);
+        assert!(compressed.len() < data.len());
+        assert_eq!(
+            brotli_compressed_len(&data, BROTLI_MAX_LEVEL),
+            compressed.len() as u64
+        );
+        assert_eq!(brotli_decompress(&compressed, 1_000).unwrap(), data);
+        assert_eq!(brotli_decompress(&compressed, 999), None);
+        assert_eq!(brotli_decompress(&[0xff, 0xff], 1_000), None);
+    }
+}
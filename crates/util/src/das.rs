@@ -0,0 +1,474 @@
+#![allow(dead_code)]
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Bytes, B256};
+use thiserror::Error;
+
+use crate::sequencer_batch::{
+    BatchHeader, BatchPayloadKind, DAS_MESSAGE_HEADER_FLAG, TREE_DAS_MESSAGE_HEADER_FLAG,
+};
+
+pub const BLS_SIGNATURE_LEN: usize = 96;
+pub const MAX_KEYSET_KEYS: u64 = 64;
+pub const MIN_LIFETIME_SECONDS_FOR_DATA_AVAILABILITY_CERT: u64 = 7 * 24 * 60 * 60;
+
+/// Size of the bins `dastree_hash` splits its preimage into.
+pub const DASTREE_BIN_SIZE: usize = 64 * 1024;
+const DASTREE_NODE_BYTE: u8 = 0xff;
+const DASTREE_LEAF_BYTE: u8 = 0xfe;
+
+fn flip_first_bit(mut hash: B256) -> B256 {
+    hash.0[0] ^= 0x80;
+    hash
+}
+
+fn leaf_hash(bin: &[u8]) -> B256 {
+    let mut input = [0u8; 33];
+    input[0] = DASTREE_LEAF_BYTE;
+    input[1..].copy_from_slice(keccak256(bin).as_slice());
+    keccak256(input)
+}
+
+/// Nitro's `dastree.Hash`: a keccak merkle tree over 64 kB bins, with the
+/// lengths folded into interior nodes and the root's first bit flipped.
+pub fn dastree_hash(preimage: &[u8]) -> B256 {
+    if preimage.is_empty() {
+        return flip_first_bit(leaf_hash(&[]));
+    }
+    let mut layer: Vec<(B256, u32)> = preimage
+        .chunks(DASTREE_BIN_SIZE)
+        .map(|bin| (leaf_hash(bin), bin.len() as u32))
+        .collect();
+    while layer.len() > 1 {
+        layer = layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [(left, left_size), (right, right_size)] => {
+                    let size = left_size + right_size;
+                    let mut input = Vec::with_capacity(1 + 32 + 32 + 4);
+                    input.push(DASTREE_NODE_BYTE);
+                    input.extend_from_slice(left.as_slice());
+                    input.extend_from_slice(right.as_slice());
+                    input.extend_from_slice(&size.to_be_bytes());
+                    (keccak256(&input), size)
+                }
+                [odd] => *odd,
+                _ => unreachable!("chunks(2) yields one or two nodes"),
+            })
+            .collect();
+    }
+    flip_first_bit(layer[0].0)
+}
+
+/// Nitro's `FlatHashToTreeHash`: the tree hash of a single leaf whose inner
+/// hash is `flat`, used to look up data certified by version 0 certificates.
+pub fn flat_hash_to_tree_hash(flat: B256) -> B256 {
+    let mut input = [0u8; 33];
+    input[0] = DASTREE_LEAF_BYTE;
+    input[1..].copy_from_slice(flat.as_slice());
+    flip_first_bit(keccak256(input))
+}
+
+/// Nitro's `dastree.ValidHash`: accepts tree hashes and, for preimages that
+/// can't be mistaken for tree nodes, plain keccak hashes.
+pub fn valid_dastree_hash(hash: B256, preimage: &[u8]) -> bool {
+    if hash == dastree_hash(preimage) {
+        return true;
+    }
+    match preimage.first() {
+        Some(&kind) => {
+            kind != DASTREE_NODE_BYTE && kind != DASTREE_LEAF_BYTE && hash == keccak256(preimage)
+        }
+        None => false,
+    }
+}
+
+/// Resolves DAS data by hash, e.g. from a local REST cache or file dump.
+pub trait DasContentStore {
+    fn get_by_hash(&self, hash: B256) -> Option<Bytes>;
+}
+
+impl DasContentStore for BTreeMap<B256, Bytes> {
+    fn get_by_hash(&self, hash: B256) -> Option<Bytes> {
+        self.get(&hash).cloned()
+    }
+}
+
+/// A committee keyset: the BLS public keys of its members and how many of
+/// them are assumed honest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DasKeyset {
+    pub assumed_honest: u64,
+    pub pub_keys: Vec<Bytes>,
+}
+
+impl DasKeyset {
+    pub fn parse(data: &[u8]) -> Result<Self, DasError> {
+        let mut rd = data;
+        let assumed_honest = read_u64(&mut rd)?;
+        let num_keys = read_u64(&mut rd)?;
+        if num_keys > MAX_KEYSET_KEYS {
+            return Err(DasError::TooManyKeys(num_keys));
+        }
+        let pub_keys = (0..num_keys)
+            .map(|_| {
+                let len = read_bytes(&mut rd, 2)?;
+                let len = usize::from(u16::from_be_bytes([len[0], len[1]]));
+                Ok(Bytes::copy_from_slice(read_bytes(&mut rd, len)?))
+            })
+            .collect::<Result<_, DasError>>()?;
+        Ok(Self {
+            assumed_honest,
+            pub_keys,
+        })
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.assumed_honest.to_be_bytes());
+        out.extend_from_slice(&(self.pub_keys.len() as u64).to_be_bytes());
+        for key in &self.pub_keys {
+            out.extend_from_slice(&(key.len() as u16).to_be_bytes());
+            out.extend_from_slice(key);
+        }
+        out
+    }
+
+    pub fn hash(&self) -> Result<B256, DasError> {
+        let serialized = self.serialize();
+        if serialized.len() > DASTREE_BIN_SIZE {
+            return Err(DasError::KeysetTooLarge);
+        }
+        Ok(dastree_hash(&serialized))
+    }
+
+    /// Checks that fewer members than are assumed honest stayed out of
+    /// `signers_mask`. The aggregated BLS signature itself is not verified.
+    pub fn check_signers(&self, signers_mask: u64) -> Result<(), DasError> {
+        let non_signers = (0..self.pub_keys.len())
+            .filter(|i| signers_mask & (1 << i) == 0)
+            .count() as u64;
+        if non_signers >= self.assumed_honest {
+            return Err(DasError::NotEnoughSigners {
+                non_signers,
+                assumed_honest: self.assumed_honest,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DasCertificate {
+    pub header: u8,
+    pub keyset_hash: B256,
+    pub data_hash: B256,
+    pub timeout: u64,
+    /// 0 for certificates over `keccak256(data)`, 1 for `dastree_hash(data)`.
+    /// Only present on the wire with the tree DAS header flag.
+    pub version: u8,
+    pub signers_mask: u64,
+    pub signature: [u8; BLS_SIGNATURE_LEN],
+}
+
+impl DasCertificate {
+    /// Parses a certificate from a batch payload, starting at its flag byte.
+    pub fn parse(data: &[u8]) -> Result<Self, DasError> {
+        let mut rd = data;
+        let header = read_bytes(&mut rd, 1)?[0];
+        if header & DAS_MESSAGE_HEADER_FLAG == 0 {
+            return Err(DasError::NotDasCertificate(header));
+        }
+        let keyset_hash = B256::from_slice(read_bytes(&mut rd, 32)?);
+        let data_hash = B256::from_slice(read_bytes(&mut rd, 32)?);
+        let timeout = read_u64(&mut rd)?;
+        let version = if header & TREE_DAS_MESSAGE_HEADER_FLAG != 0 {
+            read_bytes(&mut rd, 1)?[0]
+        } else {
+            0
+        };
+        let signers_mask = read_u64(&mut rd)?;
+        let mut signature = [0u8; BLS_SIGNATURE_LEN];
+        signature.copy_from_slice(read_bytes(&mut rd, BLS_SIGNATURE_LEN)?);
+        Ok(Self {
+            header,
+            keyset_hash,
+            data_hash,
+            timeout,
+            version,
+            signers_mask,
+            signature,
+        })
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + 32 + 32 + 8 + 1 + 8 + BLS_SIGNATURE_LEN);
+        out.push(self.header);
+        out.extend_from_slice(self.keyset_hash.as_slice());
+        out.extend_from_slice(self.data_hash.as_slice());
+        out.extend_from_slice(&self.timeout.to_be_bytes());
+        if self.header & TREE_DAS_MESSAGE_HEADER_FLAG != 0 {
+            out.push(self.version);
+        }
+        out.extend_from_slice(&self.signers_mask.to_be_bytes());
+        out.extend_from_slice(&self.signature);
+        out
+    }
+
+    /// The message the committee signs.
+    pub fn signable_fields(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(32 + 8 + 1);
+        out.extend_from_slice(self.data_hash.as_slice());
+        out.extend_from_slice(&self.timeout.to_be_bytes());
+        if self.version != 0 {
+            out.push(self.version);
+        }
+        out
+    }
+
+    /// Fetches the preimage of `hash` the way this certificate's version
+    /// commits to it.
+    pub fn get_by_hash<S: DasContentStore>(
+        &self,
+        store: &S,
+        hash: B256,
+    ) -> Result<Bytes, DasError> {
+        let preimage = match self.version {
+            0 => store
+                .get_by_hash(flat_hash_to_tree_hash(hash))
+                .or_else(|| store.get_by_hash(hash)),
+            _ => store.get_by_hash(hash),
+        }
+        .ok_or(DasError::MissingPreimage(hash))?;
+        let valid = match self.version {
+            0 => keccak256(&preimage) == hash,
+            _ => dastree_hash(&preimage) == hash,
+        };
+        if !valid {
+            return Err(DasError::HashMismatch(hash));
+        }
+        Ok(preimage)
+    }
+
+    pub fn recover_keyset<S: DasContentStore>(&self, store: &S) -> Result<DasKeyset, DasError> {
+        DasKeyset::parse(&self.get_by_hash(store, self.keyset_hash)?)
+    }
+}
+
+/// Nitro's `RecoverPayloadFromDasBatch`: resolves the certificate's keyset
+/// and data from `store` and returns the header with the recovered payload
+/// for `decode_resolved_batch`. Certificates Nitro discards, for their
+/// version, too few signers or too short a lifetime, recover an empty
+/// payload, so the batch still sequences its delayed messages.
+pub fn resolve_das_batch<S: DasContentStore>(
+    data: &[u8],
+    store: &S,
+) -> Result<(BatchHeader, Bytes), DasError> {
+    let (header, payload) = BatchHeader::parse(data).map_err(|_| DasError::Truncated)?;
+    if BatchPayloadKind::classify(payload) != Ok(BatchPayloadKind::Das) {
+        return Err(DasError::NotDasCertificate(
+            payload.first().copied().unwrap_or_default(),
+        ));
+    }
+    let cert = DasCertificate::parse(payload)?;
+    if cert.version >= 2 {
+        return Ok((header, Bytes::new()));
+    }
+    if cert
+        .recover_keyset(store)?
+        .check_signers(cert.signers_mask)
+        .is_err()
+    {
+        return Ok((header, Bytes::new()));
+    }
+    if cert.timeout
+        < header
+            .max_timestamp
+            .saturating_add(MIN_LIFETIME_SECONDS_FOR_DATA_AVAILABILITY_CERT)
+    {
+        return Ok((header, Bytes::new()));
+    }
+    Ok((header, cert.get_by_hash(store, cert.data_hash)?))
+}
+
+fn read_bytes<'a>(rd: &mut &'a [u8], n: usize) -> Result<&'a [u8], DasError> {
+    if rd.len() < n {
+        return Err(DasError::Truncated);
+    }
+    let (head, rest) = rd.split_at(n);
+    *rd = rest;
+    Ok(head)
+}
+
+fn read_u64(rd: &mut &[u8]) -> Result<u64, DasError> {
+    let b = read_bytes(rd, 8)?;
+    Ok(u64::from_be_bytes(b.try_into().expect("8 bytes")))
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum DasError {
+    #[error("DAS data truncated")]
+    Truncated,
+    #[error("header byte {0:#04x} is not a DAS certificate")]
+    NotDasCertificate(u8),
+    #[error("too many keys in keyset: {0}")]
+    TooManyKeys(u64),
+    #[error("keyset too large")]
+    KeysetTooLarge,
+    #[error("no preimage for {0}")]
+    MissingPreimage(B256),
+    #[error("preimage does not match {0}")]
+    HashMismatch(B256),
+    #[error("{non_signers} non-signers with {assumed_honest} assumed honest")]
+    NotEnoughSigners {
+        non_signers: u64,
+        assumed_honest: u64,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyset() -> DasKeyset {
+        DasKeyset {
+            assumed_honest: 2,
+            pub_keys: (1..=3u8).map(|i| Bytes::from(alloc::vec![i; 96])).collect(),
+        }
+    }
+
+    fn cert(keyset_hash: B256, data_hash: B256, version: u8) -> DasCertificate {
+        DasCertificate {
+            header: DAS_MESSAGE_HEADER_FLAG | TREE_DAS_MESSAGE_HEADER_FLAG,
+            keyset_hash,
+            data_hash,
+            timeout: 2_000_000,
+            version,
+            signers_mask: 0b011,
+            signature: [7u8; BLS_SIGNATURE_LEN],
+        }
+    }
+
+    #[test]
+    fn dastree_hash_of_small_and_empty_preimages() {
+        let data = b"hello";
+        let expected = flip_first_bit(leaf_hash(data));
+        assert_eq!(dastree_hash(data), expected);
+        assert_eq!(dastree_hash(&[]), flip_first_bit(leaf_hash(&[])));
+        assert!(valid_dastree_hash(dastree_hash(data), data));
+        assert!(valid_dastree_hash(keccak256(data), data));
+        assert!(!valid_dastree_hash(keccak256([0xfe]), &[0xfe]));
+        assert_eq!(flat_hash_to_tree_hash(keccak256(data)), dastree_hash(data));
+    }
+
+    #[test]
+    fn dastree_hash_pairs_bins_and_carries_odd_one() {
+        let data = alloc::vec![1u8; 2 * DASTREE_BIN_SIZE + 10];
+        let leaves: Vec<_> = data.chunks(DASTREE_BIN_SIZE).map(leaf_hash).collect();
+        let mut input = alloc::vec![DASTREE_NODE_BYTE];
+        input.extend_from_slice(leaves[0].as_slice());
+        input.extend_from_slice(leaves[1].as_slice());
+        input.extend_from_slice(&(2 * DASTREE_BIN_SIZE as u32).to_be_bytes());
+        let pair = keccak256(&input);
+        let mut input = alloc::vec![DASTREE_NODE_BYTE];
+        input.extend_from_slice(pair.as_slice());
+        input.extend_from_slice(leaves[2].as_slice());
+        input.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        assert_eq!(dastree_hash(&data), flip_first_bit(keccak256(&input)));
+    }
+
+    #[test]
+    fn keyset_roundtrip_and_threshold() {
+        let ks = keyset();
+        assert_eq!(DasKeyset::parse(&ks.serialize()).unwrap(), ks);
+        assert_eq!(ks.hash().unwrap(), dastree_hash(&ks.serialize()));
+        assert!(ks.check_signers(0b011).is_ok());
+        assert_eq!(
+            ks.check_signers(0b001),
+            Err(DasError::NotEnoughSigners {
+                non_signers: 2,
+                assumed_honest: 2
+            })
+        );
+        let mut too_many = 0u64.to_be_bytes().to_vec();
+        too_many.extend_from_slice(&65u64.to_be_bytes());
+        assert_eq!(DasKeyset::parse(&too_many), Err(DasError::TooManyKeys(65)));
+    }
+
+    #[test]
+    fn certificate_roundtrip_by_header_flag() {
+        let c = cert(B256::repeat_byte(1), B256::repeat_byte(2), 1);
+        let bytes = c.serialize();
+        assert_eq!(bytes.len(), 1 + 32 + 32 + 8 + 1 + 8 + 96);
+        assert_eq!(DasCertificate::parse(&bytes).unwrap(), c);
+        assert_eq!(c.signable_fields().len(), 41);
+
+        let mut legacy = cert(B256::repeat_byte(1), B256::repeat_byte(2), 0);
+        legacy.header = DAS_MESSAGE_HEADER_FLAG;
+        assert_eq!(legacy.serialize().len(), bytes.len() - 1);
+        assert_eq!(DasCertificate::parse(&legacy.serialize()).unwrap(), legacy);
+        assert_eq!(legacy.signable_fields().len(), 40);
+        assert_eq!(
+            DasCertificate::parse(&[0x00]),
+            Err(DasError::NotDasCertificate(0))
+        );
+    }
+
+    #[test]
+    fn resolves_payload_from_store() {
+        let ks = keyset().serialize();
+        let payload = alloc::vec![0u8, 1, 2, 3];
+        let mut store = BTreeMap::new();
+        store.insert(dastree_hash(&ks), Bytes::from(ks.clone()));
+        store.insert(dastree_hash(&payload), Bytes::from(payload.clone()));
+
+        let header = BatchHeader {
+            max_timestamp: 1_000,
+            ..Default::default()
+        };
+        let c = cert(dastree_hash(&ks), dastree_hash(&payload), 1);
+        let mut data = header.encode().to_vec();
+        data.extend_from_slice(&c.serialize());
+        let (parsed, resolved) = resolve_das_batch(&data, &store).unwrap();
+        assert_eq!(parsed, header);
+        assert_eq!(resolved, Bytes::from(payload.clone()));
+
+        // Certificates Nitro discards leave the batch without a payload.
+        let mut short = c.clone();
+        short.timeout = 1_000 + MIN_LIFETIME_SECONDS_FOR_DATA_AVAILABILITY_CERT - 1;
+        let mut unsigned = c.clone();
+        unsigned.signers_mask = 0b001;
+        let future = cert(B256::repeat_byte(8), B256::repeat_byte(9), 2);
+        for discarded in [short, unsigned, future] {
+            let mut data = header.encode().to_vec();
+            data.extend_from_slice(&discarded.serialize());
+            assert_eq!(resolve_das_batch(&data, &store), Ok((header, Bytes::new())));
+        }
+
+        let missing = cert(dastree_hash(&ks), B256::repeat_byte(9), 1);
+        let mut data = header.encode().to_vec();
+        data.extend_from_slice(&missing.serialize());
+        assert_eq!(
+            resolve_das_batch(&data, &store),
+            Err(DasError::MissingPreimage(B256::repeat_byte(9)))
+        );
+    }
+
+    #[test]
+    fn version_zero_looks_up_flat_hashes() {
+        let payload = b"legacy payload".to_vec();
+        let mut store = BTreeMap::new();
+        store.insert(keccak256(&payload), Bytes::from(payload.clone()));
+        let c = cert(B256::ZERO, keccak256(&payload), 0);
+        assert_eq!(
+            c.get_by_hash(&store, c.data_hash).unwrap(),
+            Bytes::from(payload)
+        );
+        store.insert(B256::repeat_byte(3), Bytes::from_static(b"wrong"));
+        assert_eq!(
+            c.get_by_hash(&store, B256::repeat_byte(3)),
+            Err(DasError::HashMismatch(B256::repeat_byte(3)))
+        );
+    }
+}
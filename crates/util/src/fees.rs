@@ -0,0 +1,247 @@
+#![allow(dead_code)]
+
+use alloy_primitives::U256;
+use arb_alloy_consensus::tx::ArbTxEnvelope;
+use arb_alloy_consensus::{ArbOsVersion, ArbReceiptEnvelope};
+
+use crate::l1_pricing::tx_type_has_poster_costs;
+use crate::retryables::{retry_tx_refunds, RetryRefundContext};
+
+/// Block and chain state ArbOS reads when settling a transaction's fees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeContext {
+    pub base_fee: U256,
+    pub min_base_fee: U256,
+    pub arbos_version: ArbOsVersion,
+    pub infra_fee_account_set: bool,
+    /// The poster cost `L1PricingState` charged the tx, in wei.
+    pub poster_cost: U256,
+}
+
+/// Where a transaction's fees went, as ArbOS's end-of-tx hook mints them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub gas_used: u64,
+    /// Gas bought up front to cover the poster cost.
+    pub poster_gas: u64,
+    /// Base fee times the gas used for execution.
+    pub l2_execution_fee: U256,
+    /// Paid to the L1 pricer's funds pool.
+    pub l1_poster_fee: U256,
+    /// The infra fee account's share of the execution fee.
+    pub infra_fee: U256,
+    /// The network fee account's share of the execution fee.
+    pub network_fee: U256,
+    /// Returned for gas bought but not used.
+    pub refund: U256,
+}
+
+impl FeeBreakdown {
+    pub fn total_fee(&self) -> U256 {
+        self.l2_execution_fee.saturating_add(self.l1_poster_fee)
+    }
+}
+
+/// The gas a receipt's transaction used, given the cumulative gas used of the
+/// receipt before it in the block, or zero for the first.
+pub fn receipt_gas_used(receipt: &ArbReceiptEnvelope, prev_cumulative_gas_used: u128) -> u64 {
+    u64::try_from(
+        receipt
+            .cumulative_gas_used
+            .saturating_sub(prev_cumulative_gas_used),
+    )
+    .unwrap_or(u64::MAX)
+}
+
+/// The breakdown of a transaction that ran through the EVM and bought
+/// `gas_limit` gas at the base fee; tips are dropped on Arbitrum.
+pub fn fee_breakdown(gas_limit: u64, gas_used: u64, ctx: &FeeContext) -> FeeBreakdown {
+    let charge_infra = ctx.arbos_version.is_at_least(ArbOsVersion::V5) && ctx.infra_fee_account_set;
+    split_fees(gas_limit, gas_used, ctx, charge_infra)
+}
+
+fn split_fees(gas_limit: u64, gas_used: u64, ctx: &FeeContext, charge_infra: bool) -> FeeBreakdown {
+    let base_fee = ctx.base_fee;
+    let poster_gas = ctx
+        .poster_cost
+        .checked_div(base_fee)
+        .map_or(0, |gas| gas.saturating_to::<u64>());
+    let total_cost = base_fee.saturating_mul(U256::from(gas_used));
+    let mut poster_fee = base_fee.saturating_mul(U256::from(poster_gas));
+    let compute_cost = match total_cost.checked_sub(poster_fee) {
+        Some(cost) => cost,
+        None => {
+            poster_fee = U256::ZERO;
+            total_cost
+        }
+    };
+
+    let mut network_fee = compute_cost;
+    let mut infra_fee = U256::ZERO;
+    if charge_infra {
+        let compute_gas = gas_used.saturating_sub(poster_gas);
+        let infra_compute_cost = ctx
+            .min_base_fee
+            .min(base_fee)
+            .saturating_mul(U256::from(compute_gas));
+        infra_fee = infra_compute_cost.min(network_fee);
+        network_fee -= infra_fee;
+    }
+
+    FeeBreakdown {
+        gas_used,
+        poster_gas,
+        l2_execution_fee: compute_cost,
+        l1_poster_fee: poster_fee,
+        infra_fee,
+        network_fee,
+        refund: base_fee.saturating_mul(U256::from(gas_limit.saturating_sub(gas_used))),
+    }
+}
+
+/// The breakdown for an Arbitrum transaction and its receipt. Txs without
+/// poster costs ignore `ctx.poster_cost`; ArbOS-handled txs pay nothing here,
+/// and retries refund out of the fee accounts that prepaid their gas.
+pub fn arb_tx_fee_breakdown(
+    tx: &ArbTxEnvelope,
+    receipt: &ArbReceiptEnvelope,
+    prev_cumulative_gas_used: u128,
+    ctx: &FeeContext,
+) -> FeeBreakdown {
+    let gas_used = receipt_gas_used(receipt, prev_cumulative_gas_used);
+    let no_poster = FeeContext {
+        poster_cost: U256::ZERO,
+        ..ctx.clone()
+    };
+    let ctx = if tx_type_has_poster_costs(tx.tx_type().as_u8()) {
+        ctx
+    } else {
+        &no_poster
+    };
+    match tx {
+        ArbTxEnvelope::Unsigned(tx) => fee_breakdown(tx.gas, gas_used, ctx),
+        ArbTxEnvelope::Contract(tx) => fee_breakdown(tx.gas, gas_used, ctx),
+        ArbTxEnvelope::Retry(retry) => {
+            let refunds = retry_tx_refunds(
+                retry,
+                &RetryRefundContext {
+                    gas_used,
+                    success: receipt.status,
+                    min_base_fee: ctx.min_base_fee,
+                    arbos_version: ctx.arbos_version,
+                    infra_fee_account_set: ctx.infra_fee_account_set,
+                },
+            );
+            // Retries settle with the infra split of their refunds.
+            let charge_infra = ctx.arbos_version.has_infra_fees() && ctx.infra_fee_account_set;
+            FeeBreakdown {
+                refund: refunds.submission_fee.total()
+                    + refunds.infra_fee.total()
+                    + refunds.network_fee.total(),
+                ..split_fees(retry.gas, gas_used, ctx, charge_infra)
+            }
+        }
+        ArbTxEnvelope::Deposit(_)
+        | ArbTxEnvelope::SubmitRetryable(_)
+        | ArbTxEnvelope::Internal(_)
+        | ArbTxEnvelope::Legacy(_) => FeeBreakdown {
+            gas_used,
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, Bytes, B256};
+    use arb_alloy_consensus::tx::{ArbRetryTx, ArbUnsignedTx};
+
+    fn ctx(poster_cost: u64) -> FeeContext {
+        FeeContext {
+            base_fee: U256::from(200_000_000u64),
+            min_base_fee: U256::from(100_000_000u64),
+            arbos_version: ArbOsVersion::V20,
+            infra_fee_account_set: true,
+            poster_cost: U256::from(poster_cost),
+        }
+    }
+
+    fn receipt(status: bool, cumulative_gas_used: u128) -> ArbReceiptEnvelope {
+        ArbReceiptEnvelope {
+            status,
+            cumulative_gas_used,
+            logs_bloom: [0; 256],
+            logs: Default::default(),
+        }
+    }
+
+    #[test]
+    fn splits_poster_infra_and_network_fees() {
+        // 1_000 poster gas plus a wei that doesn't buy a whole gas unit.
+        let b = fee_breakdown(100_000, 50_000, &ctx(200_000_000_001));
+        assert_eq!(b.poster_gas, 1_000);
+        assert_eq!(b.l1_poster_fee, U256::from(200_000_000_000u64));
+        assert_eq!(b.l2_execution_fee, U256::from(49_000u64 * 200_000_000));
+        assert_eq!(b.infra_fee, U256::from(49_000u64 * 100_000_000));
+        assert_eq!(b.network_fee, U256::from(49_000u64 * 100_000_000));
+        assert_eq!(b.refund, U256::from(50_000u64 * 200_000_000));
+        assert_eq!(b.total_fee(), U256::from(50_000u64 * 200_000_000));
+
+        let no_infra = FeeContext {
+            arbos_version: ArbOsVersion::V3,
+            ..ctx(0)
+        };
+        let b = fee_breakdown(50_000, 50_000, &no_infra);
+        assert_eq!(b.infra_fee, U256::ZERO);
+        assert_eq!(b.network_fee, b.l2_execution_fee);
+    }
+
+    #[test]
+    fn poster_fee_above_total_goes_to_network() {
+        let b = fee_breakdown(10_000, 500, &ctx(200_000_000_000));
+        assert_eq!(b.l1_poster_fee, U256::ZERO);
+        assert_eq!(b.l2_execution_fee, U256::from(500u64 * 200_000_000));
+    }
+
+    #[test]
+    fn arb_txs_use_receipt_gas_and_skip_poster_costs() {
+        let tx = ArbTxEnvelope::Unsigned(ArbUnsignedTx {
+            chain_id: U256::from(1),
+            from: Address::with_last_byte(1),
+            nonce: 0,
+            gas_fee_cap: U256::from(200_000_000u64),
+            gas: 30_000,
+            to: Some(Address::with_last_byte(2)),
+            value: U256::ZERO,
+            data: Bytes::new(),
+        });
+        let b = arb_tx_fee_breakdown(&tx, &receipt(true, 121_000), 100_000, &ctx(1 << 40));
+        assert_eq!(b.gas_used, 21_000);
+        assert_eq!(b.poster_gas, 0);
+        assert_eq!(b.refund, U256::from(9_000u64 * 200_000_000));
+
+        let retry = ArbRetryTx {
+            chain_id: U256::from(1),
+            nonce: 0,
+            from: Address::with_last_byte(1),
+            gas_fee_cap: U256::from(200_000_000u64),
+            gas: 100_000,
+            to: Some(Address::with_last_byte(2)),
+            value: U256::ZERO,
+            data: Bytes::new(),
+            ticket_id: B256::ZERO,
+            refund_to: Address::with_last_byte(3),
+            max_refund: U256::from(10u64).pow(U256::from(18)),
+            submission_fee_refund: U256::from(1_000u64),
+        };
+        let b = arb_tx_fee_breakdown(
+            &ArbTxEnvelope::Retry(retry),
+            &receipt(true, 40_000),
+            0,
+            &ctx(0),
+        );
+        assert_eq!(b.refund, U256::from(60_000u64 * 200_000_000 + 1_000));
+        assert_eq!(b.infra_fee, U256::from(40_000u64 * 100_000_000));
+    }
+}
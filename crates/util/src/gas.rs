@@ -0,0 +1,317 @@
+#![allow(dead_code)]
+
+use alloy_primitives::{Bytes, U256};
+use arb_alloy_consensus::tx::ArbTxEnvelope;
+use arb_alloy_consensus::ArbOsVersion;
+use thiserror::Error;
+
+use crate::l1_pricing::TX_DATA_NONZERO_GAS_EIP2028;
+use crate::l2_pricing::L2PricingState;
+
+pub const TX_GAS: u64 = 21_000;
+pub const TX_GAS_CONTRACT_CREATION: u64 = 53_000;
+pub const TX_DATA_ZERO_GAS: u64 = 4;
+pub const INIT_CODE_WORD_GAS: u64 = 2;
+pub const TX_TOKEN_PER_NON_ZERO_BYTE: u64 = 4;
+pub const TX_COST_FLOOR_PER_TOKEN: u64 = 10;
+pub const MAX_CODE_SIZE: usize = 24_576;
+pub const MAX_INIT_CODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
+/// Shanghai's initcode metering and size limit arrived with ArbOS 11.
+fn has_shanghai(arbos_version: ArbOsVersion) -> bool {
+    arbos_version.is_at_least(ArbOsVersion::V11)
+}
+
+/// Geth's `IntrinsicGas` for a call or creation without an access list, or
+/// `None` on overflow.
+pub fn call_intrinsic_gas(
+    data: &[u8],
+    is_create: bool,
+    arbos_version: ArbOsVersion,
+) -> Option<u64> {
+    let base = if is_create {
+        TX_GAS_CONTRACT_CREATION
+    } else {
+        TX_GAS
+    };
+    let non_zero = data.iter().filter(|b| **b != 0).count() as u64;
+    let zero = data.len() as u64 - non_zero;
+    let mut gas = base
+        .checked_add(non_zero.checked_mul(TX_DATA_NONZERO_GAS_EIP2028)?)?
+        .checked_add(zero.checked_mul(TX_DATA_ZERO_GAS)?)?;
+    if is_create && has_shanghai(arbos_version) {
+        let words = (data.len() as u64).div_ceil(32);
+        gas = gas.checked_add(words.checked_mul(INIT_CODE_WORD_GAS)?)?;
+    }
+    Some(gas)
+}
+
+/// EIP-7623's floor on the gas a tx with `data` must buy.
+pub fn floor_data_gas(data: &[u8]) -> Option<u64> {
+    let non_zero = data.iter().filter(|b| **b != 0).count() as u64;
+    let zero = data.len() as u64 - non_zero;
+    let tokens = non_zero
+        .checked_mul(TX_TOKEN_PER_NON_ZERO_BYTE)?
+        .checked_add(zero)?;
+    TX_GAS.checked_add(tokens.checked_mul(TX_COST_FLOOR_PER_TOKEN)?)
+}
+
+/// The gas limit, destination and calldata of txs that go through the EVM.
+/// Deposits, internal txs and retryable submissions end in ArbOS's start
+/// hook, and classic legacy txs are only carried over from before Nitro.
+fn evm_call(tx: &ArbTxEnvelope) -> Option<(u64, bool, &Bytes, U256)> {
+    match tx {
+        ArbTxEnvelope::Unsigned(tx) => Some((tx.gas, tx.to.is_none(), &tx.data, tx.gas_fee_cap)),
+        ArbTxEnvelope::Contract(tx) => Some((tx.gas, tx.to.is_none(), &tx.data, tx.gas_fee_cap)),
+        ArbTxEnvelope::Retry(tx) => Some((tx.gas, tx.to.is_none(), &tx.data, tx.gas_fee_cap)),
+        ArbTxEnvelope::SubmitRetryable(_)
+        | ArbTxEnvelope::Deposit(_)
+        | ArbTxEnvelope::Internal(_)
+        | ArbTxEnvelope::Legacy(_) => None,
+    }
+}
+
+/// Intrinsic gas as Arbitrum charges it: free for txs ArbOS handles without
+/// the EVM, and geth's rules for the rest, retries included.
+pub fn intrinsic_gas(tx: &ArbTxEnvelope, arbos_version: ArbOsVersion) -> Result<u64, GasError> {
+    match evm_call(tx) {
+        None => Ok(0),
+        Some((_, is_create, data, _)) => {
+            call_intrinsic_gas(data, is_create, arbos_version).ok_or(GasError::GasUintOverflow)
+        }
+    }
+}
+
+/// The checks a tx can fail without looking at state. `base_fee` adds the
+/// fee cap check when known. `calldata_price_increase` is the chain's
+/// `CalldataPriceIncrease` feature, which applies the EIP-7623 calldata
+/// floor from ArbOS 40. Returns the intrinsic gas.
+pub fn validate_tx_gas(
+    tx: &ArbTxEnvelope,
+    arbos_version: ArbOsVersion,
+    l2_pricing: &L2PricingState,
+    base_fee: Option<U256>,
+    calldata_price_increase: bool,
+) -> Result<u64, GasError> {
+    let Some((gas, is_create, data, gas_fee_cap)) = evm_call(tx) else {
+        return Ok(0);
+    };
+    if is_create && has_shanghai(arbos_version) && data.len() > MAX_INIT_CODE_SIZE {
+        return Err(GasError::MaxInitCodeSizeExceeded {
+            size: data.len(),
+            max: MAX_INIT_CODE_SIZE,
+        });
+    }
+    let intrinsic = intrinsic_gas(tx, arbos_version)?;
+    if gas < intrinsic {
+        return Err(GasError::IntrinsicGasTooLow {
+            have: gas,
+            want: intrinsic,
+        });
+    }
+    if calldata_price_increase && arbos_version.has_calldata_price_increase() {
+        let floor = floor_data_gas(data).ok_or(GasError::GasUintOverflow)?;
+        if gas < floor {
+            return Err(GasError::FloorDataGas {
+                have: gas,
+                want: floor,
+            });
+        }
+    }
+    let max = l2_pricing.max_tx_gas_limit(arbos_version);
+    if intrinsic > max {
+        return Err(GasError::ExceedsMaxTxGas { intrinsic, max });
+    }
+    if let Some(base_fee) = base_fee {
+        if gas_fee_cap < base_fee {
+            return Err(GasError::FeeCapTooLow {
+                fee_cap: gas_fee_cap,
+                base_fee,
+            });
+        }
+    }
+    Ok(intrinsic)
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum GasError {
+    #[error("gas uint64 overflow")]
+    GasUintOverflow,
+    #[error("intrinsic gas too low: have {have}, want {want}")]
+    IntrinsicGasTooLow { have: u64, want: u64 },
+    #[error("insufficient gas for floor data gas cost: have {have}, want {want}")]
+    FloorDataGas { have: u64, want: u64 },
+    #[error("intrinsic gas {intrinsic} exceeds the per-tx gas limit {max}")]
+    ExceedsMaxTxGas { intrinsic: u64, max: u64 },
+    #[error("max initcode size exceeded: code size {size} limit {max}")]
+    MaxInitCodeSizeExceeded { size: usize, max: usize },
+    #[error("max fee per gas less than block base fee: fee cap {fee_cap}, base fee {base_fee}")]
+    FeeCapTooLow { fee_cap: U256, base_fee: U256 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use arb_alloy_consensus::tx::{ArbDepositTx, ArbRetryTx, ArbUnsignedTx};
+
+    fn unsigned(gas: u64, to: Option<Address>, data: &[u8]) -> ArbTxEnvelope {
+        ArbTxEnvelope::Unsigned(ArbUnsignedTx {
+            chain_id: U256::from(42161),
+            from: Address::with_last_byte(1),
+            nonce: 0,
+            gas_fee_cap: U256::from(100_000_000),
+            gas,
+            to,
+            value: U256::ZERO,
+            data: Bytes::copy_from_slice(data),
+        })
+    }
+
+    #[test]
+    fn charges_calldata_and_creation() {
+        let v = ArbOsVersion::V20;
+        assert_eq!(call_intrinsic_gas(&[], false, v), Some(21_000));
+        assert_eq!(
+            call_intrinsic_gas(&[0, 1, 0, 2], false, v),
+            Some(21_000 + 8 + 32)
+        );
+        // 33 bytes of initcode is two words.
+        assert_eq!(
+            call_intrinsic_gas(&[0; 33], true, v),
+            Some(53_000 + 33 * 4 + 2 * 2)
+        );
+        assert_eq!(
+            call_intrinsic_gas(&[0; 33], true, ArbOsVersion::V6),
+            Some(53_000 + 33 * 4)
+        );
+    }
+
+    #[test]
+    fn arbos_handled_txs_are_free_and_retries_are_not() {
+        let deposit = ArbTxEnvelope::Deposit(ArbDepositTx {
+            chain_id: U256::from(1),
+            l1_request_id: B256::ZERO,
+            from: Address::ZERO,
+            to: Address::with_last_byte(2),
+            value: U256::from(1),
+        });
+        assert_eq!(intrinsic_gas(&deposit, ArbOsVersion::V30), Ok(0));
+
+        let retry = ArbTxEnvelope::Retry(ArbRetryTx {
+            chain_id: U256::from(1),
+            nonce: 0,
+            from: Address::with_last_byte(1),
+            gas_fee_cap: U256::from(1),
+            gas: 100_000,
+            to: Some(Address::with_last_byte(2)),
+            value: U256::ZERO,
+            data: Bytes::from_static(&[1, 0]),
+            ticket_id: B256::ZERO,
+            refund_to: Address::ZERO,
+            max_refund: U256::ZERO,
+            submission_fee_refund: U256::ZERO,
+        });
+        assert_eq!(intrinsic_gas(&retry, ArbOsVersion::V30), Ok(21_020));
+    }
+
+    #[test]
+    fn validation_rejects_impossible_txs() {
+        let v = ArbOsVersion::V50;
+        let mut pricing = L2PricingState::initial(v);
+        let to = Some(Address::with_last_byte(3));
+
+        assert_eq!(
+            validate_tx_gas(&unsigned(21_016, to, &[1]), v, &pricing, None, false),
+            Ok(21_016)
+        );
+        assert_eq!(
+            validate_tx_gas(&unsigned(21_000, to, &[1]), v, &pricing, None, false),
+            Err(GasError::IntrinsicGasTooLow {
+                have: 21_000,
+                want: 21_016
+            })
+        );
+        assert_eq!(
+            validate_tx_gas(
+                &unsigned(30_000, None, &[0; MAX_INIT_CODE_SIZE + 1]),
+                v,
+                &pricing,
+                None,
+                false
+            ),
+            Err(GasError::MaxInitCodeSizeExceeded {
+                size: MAX_INIT_CODE_SIZE + 1,
+                max: MAX_INIT_CODE_SIZE
+            })
+        );
+        assert_eq!(
+            validate_tx_gas(
+                &unsigned(21_000, to, &[]),
+                v,
+                &pricing,
+                Some(U256::from(200_000_000)),
+                false
+            ),
+            Err(GasError::FeeCapTooLow {
+                fee_cap: U256::from(100_000_000),
+                base_fee: U256::from(200_000_000)
+            })
+        );
+
+        pricing.per_tx_gas_limit = 20_000;
+        assert_eq!(
+            validate_tx_gas(&unsigned(u64::MAX, to, &[]), v, &pricing, None, false),
+            Err(GasError::ExceedsMaxTxGas {
+                intrinsic: 21_000,
+                max: 20_000
+            })
+        );
+        // Before ArbOS 50 the block gas limit bounds each tx instead.
+        assert_eq!(
+            validate_tx_gas(
+                &unsigned(21_000, to, &[]),
+                ArbOsVersion::V40,
+                &pricing,
+                None,
+                false
+            ),
+            Ok(21_000)
+        );
+    }
+
+    #[test]
+    fn calldata_floor_needs_the_feature_and_arbos_40() {
+        let pricing = L2PricingState::initial(ArbOsVersion::V50);
+        let to = Some(Address::with_last_byte(3));
+        // 100 nonzero bytes: 1_600 intrinsic calldata gas, a 4_000 floor.
+        let tx = unsigned(23_000, to, &[1; 100]);
+        assert_eq!(floor_data_gas(&[1; 100]), Some(25_000));
+        assert_eq!(floor_data_gas(&[0, 0]), Some(21_020));
+        assert_eq!(
+            validate_tx_gas(&tx, ArbOsVersion::V40, &pricing, None, true),
+            Err(GasError::FloorDataGas {
+                have: 23_000,
+                want: 25_000
+            })
+        );
+        assert_eq!(
+            validate_tx_gas(&tx, ArbOsVersion::V40, &pricing, None, false),
+            Ok(22_600)
+        );
+        assert_eq!(
+            validate_tx_gas(&tx, ArbOsVersion::V32, &pricing, None, true),
+            Ok(22_600)
+        );
+        assert_eq!(
+            validate_tx_gas(
+                &unsigned(25_000, to, &[1; 100]),
+                ArbOsVersion::V40,
+                &pricing,
+                None,
+                true
+            ),
+            Ok(22_600)
+        );
+    }
+}
@@ -0,0 +1,233 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Address, B256, U256};
+use arb_alloy_consensus::messages::L1IncomingMessage;
+pub use arb_alloy_consensus::sequencer_batch::sequencer_batch_accumulator;
+use thiserror::Error;
+
+/// `Messages.messageHash` from the bridge contracts.
+pub fn delayed_message_hash(
+    kind: u8,
+    sender: Address,
+    block_number: u64,
+    timestamp: u64,
+    inbox_seq_num: U256,
+    base_fee_l1: U256,
+    message_data_hash: B256,
+) -> B256 {
+    let mut packed = Vec::with_capacity(1 + 20 + 8 + 8 + 32 + 32 + 32);
+    packed.push(kind);
+    packed.extend_from_slice(sender.as_slice());
+    packed.extend_from_slice(&block_number.to_be_bytes());
+    packed.extend_from_slice(&timestamp.to_be_bytes());
+    packed.extend_from_slice(&inbox_seq_num.to_be_bytes::<32>());
+    packed.extend_from_slice(&base_fee_l1.to_be_bytes::<32>());
+    packed.extend_from_slice(message_data_hash.as_slice());
+    keccak256(packed)
+}
+
+/// `Messages.messageHash` of a delayed message, whose request id is its
+/// sequence number in the delayed inbox.
+pub fn incoming_message_hash(msg: &L1IncomingMessage) -> Result<B256, InboxError> {
+    let header = &msg.header;
+    let request_id = header.request_id.ok_or(InboxError::MissingRequestId)?;
+    Ok(delayed_message_hash(
+        header.kind,
+        header.poster,
+        header.block_number,
+        header.timestamp,
+        request_id.into(),
+        header.l1_base_fee,
+        keccak256(&msg.l2_msg),
+    ))
+}
+
+/// `Messages.accumulateInboxMessage`.
+pub fn accumulate_inbox_message(prev_acc: B256, message_hash: B256) -> B256 {
+    let mut packed = [0u8; 64];
+    packed[..32].copy_from_slice(prev_acc.as_slice());
+    packed[32..].copy_from_slice(message_hash.as_slice());
+    keccak256(packed)
+}
+
+/// Folds message hashes into the accumulator that follows `prev_acc`.
+pub fn fold_delayed_accumulator<I>(prev_acc: B256, message_hashes: I) -> B256
+where
+    I: IntoIterator<Item = B256>,
+{
+    message_hashes
+        .into_iter()
+        .fold(prev_acc, accumulate_inbox_message)
+}
+
+/// The data hash the sequencer inbox records for a batch: the keccak of the
+/// 40-byte header followed by the batch payload, as the batch decoder sees it.
+pub fn batch_data_hash(sequencer_message: &[u8]) -> B256 {
+    keccak256(sequencer_message)
+}
+
+/// Mirrors the bridge's `delayedInboxAccs` and `sequencerInboxAccs`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BridgeAccumulators {
+    pub delayed_inbox_accs: Vec<B256>,
+    pub sequencer_inbox_accs: Vec<B256>,
+}
+
+impl BridgeAccumulators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn delayed_message_count(&self) -> u64 {
+        self.delayed_inbox_accs.len() as u64
+    }
+
+    pub fn batch_count(&self) -> u64 {
+        self.sequencer_inbox_accs.len() as u64
+    }
+
+    /// The delayed accumulator after `count` messages, zero for none.
+    pub fn delayed_acc(&self, count: u64) -> Option<B256> {
+        match count {
+            0 => Some(B256::ZERO),
+            n => self.delayed_inbox_accs.get(n as usize - 1).copied(),
+        }
+    }
+
+    pub fn enqueue_delayed_hash(&mut self, message_hash: B256) -> B256 {
+        let prev = self.delayed_inbox_accs.last().copied().unwrap_or_default();
+        let acc = accumulate_inbox_message(prev, message_hash);
+        self.delayed_inbox_accs.push(acc);
+        acc
+    }
+
+    /// Appends a delayed message, which must carry the next sequence number
+    /// as its request id.
+    pub fn enqueue_delayed(&mut self, msg: &L1IncomingMessage) -> Result<B256, InboxError> {
+        let expected = B256::from(U256::from(self.delayed_message_count()));
+        match msg.header.request_id {
+            Some(id) if id == expected => {}
+            Some(id) => return Err(InboxError::UnexpectedRequestId { expected, got: id }),
+            None => return Err(InboxError::MissingRequestId),
+        }
+        Ok(self.enqueue_delayed_hash(incoming_message_hash(msg)?))
+    }
+
+    pub fn enqueue_sequencer_message(
+        &mut self,
+        data_hash: B256,
+        after_delayed_messages_read: u64,
+    ) -> Result<B256, InboxError> {
+        let delayed_acc = self.delayed_acc(after_delayed_messages_read).ok_or(
+            InboxError::DelayedMessagesNotYetEnqueued {
+                read: after_delayed_messages_read,
+                count: self.delayed_message_count(),
+            },
+        )?;
+        let before = self
+            .sequencer_inbox_accs
+            .last()
+            .copied()
+            .unwrap_or_default();
+        let acc = sequencer_batch_accumulator(before, data_hash, delayed_acc);
+        self.sequencer_inbox_accs.push(acc);
+        Ok(acc)
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum InboxError {
+    #[error("delayed message has no request id")]
+    MissingRequestId,
+    #[error("delayed message request id {got} does not match sequence number {expected}")]
+    UnexpectedRequestId { expected: B256, got: B256 },
+    #[error("batch reads {read} delayed messages but only {count} were enqueued")]
+    DelayedMessagesNotYetEnqueued { read: u64, count: u64 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arb_alloy_consensus::messages::{L1IncomingMessageHeader, L1MessageKind};
+
+    fn delayed(index: u64) -> L1IncomingMessage {
+        L1IncomingMessage {
+            header: L1IncomingMessageHeader {
+                kind: L1MessageKind::EthDeposit.as_u8(),
+                poster: Address::with_last_byte(0xaa),
+                block_number: 100 + index,
+                timestamp: 1_700_000_000 + index,
+                request_id: Some(B256::from(U256::from(index))),
+                l1_base_fee: U256::from(7u64),
+            },
+            l2_msg: alloc::vec![index as u8; 52].into(),
+        }
+    }
+
+    #[test]
+    fn message_hash_packs_fields_like_solidity() {
+        let msg = delayed(3);
+        let mut packed = alloc::vec![L1MessageKind::EthDeposit.as_u8()];
+        packed.extend_from_slice(Address::with_last_byte(0xaa).as_slice());
+        packed.extend_from_slice(&103u64.to_be_bytes());
+        packed.extend_from_slice(&1_700_000_003u64.to_be_bytes());
+        packed.extend_from_slice(&U256::from(3u64).to_be_bytes::<32>());
+        packed.extend_from_slice(&U256::from(7u64).to_be_bytes::<32>());
+        packed.extend_from_slice(keccak256(&msg.l2_msg).as_slice());
+        assert_eq!(packed.len(), 133);
+        assert_eq!(incoming_message_hash(&msg).unwrap(), keccak256(&packed));
+    }
+
+    #[test]
+    fn delayed_accumulator_chains_from_zero() {
+        let mut accs = BridgeAccumulators::new();
+        let hashes: Vec<_> = (0..3)
+            .map(|i| incoming_message_hash(&delayed(i)).unwrap())
+            .collect();
+        for i in 0..3 {
+            accs.enqueue_delayed(&delayed(i)).unwrap();
+        }
+        assert_eq!(
+            accs.delayed_acc(1),
+            Some(accumulate_inbox_message(B256::ZERO, hashes[0]))
+        );
+        assert_eq!(
+            accs.delayed_acc(3),
+            Some(fold_delayed_accumulator(B256::ZERO, hashes))
+        );
+        assert_eq!(accs.delayed_acc(4), None);
+        assert_eq!(
+            accs.enqueue_delayed(&delayed(5)),
+            Err(InboxError::UnexpectedRequestId {
+                expected: B256::from(U256::from(3u64)),
+                got: B256::from(U256::from(5u64)),
+            })
+        );
+    }
+
+    #[test]
+    fn sequencer_accumulator_binds_delayed_acc() {
+        let mut accs = BridgeAccumulators::new();
+        accs.enqueue_delayed(&delayed(0)).unwrap();
+        accs.enqueue_delayed(&delayed(1)).unwrap();
+
+        let first = batch_data_hash(&[0u8; 41]);
+        let acc0 = accs.enqueue_sequencer_message(first, 0).unwrap();
+        assert_eq!(
+            acc0,
+            sequencer_batch_accumulator(B256::ZERO, first, B256::ZERO)
+        );
+        let second = batch_data_hash(&[1u8; 41]);
+        let acc1 = accs.enqueue_sequencer_message(second, 2).unwrap();
+        assert_eq!(
+            acc1,
+            sequencer_batch_accumulator(acc0, second, accs.delayed_acc(2).unwrap())
+        );
+        assert_eq!(accs.batch_count(), 2);
+        assert_eq!(
+            accs.enqueue_sequencer_message(second, 3),
+            Err(InboxError::DelayedMessagesNotYetEnqueued { read: 3, count: 2 })
+        );
+    }
+}
@@ -1,12 +1,162 @@
 #![allow(dead_code)]
 
+use alloc::collections::BTreeMap;
+use alloy_primitives::{address, Address, I256, U256};
+#[cfg(feature = "brotli")]
+use arb_alloy_consensus::tx::ArbTxEnvelope;
+use arb_alloy_consensus::tx::ArbTxType;
+use arb_alloy_consensus::ArbOsVersion;
+use thiserror::Error;
+
+#[cfg(feature = "brotli")]
+use crate::compression::brotli_compressed_len;
+
 pub const TX_DATA_NONZERO_GAS_EIP2028: u64 = 16;
 pub const ONE_IN_BIPS: u64 = 10_000;
 pub const ESTIMATION_PADDING_UNITS: u64 = 16 * TX_DATA_NONZERO_GAS_EIP2028;
 pub const ESTIMATION_PADDING_BASIS_POINTS: u64 = 100;
 
+pub const BATCH_POSTER_ADDRESS: Address = address!("a4b000000000000000000073657175656e636572");
+pub const L1_PRICER_FUNDS_POOL_ADDRESS: Address =
+    address!("a4b00000000000000000000000000000000000f6");
+
+pub const DEFAULT_INITIAL_L1_BASE_FEE_WEI: u128 = 50_000_000_000;
+pub const INITIAL_INERTIA: u64 = 10;
+pub const INITIAL_PER_UNIT_REWARD: u64 = 10;
+pub const INITIAL_PER_BATCH_GAS_COST_V6: i64 = 100_000;
+pub const INITIAL_PER_BATCH_GAS_COST_V12: i64 = 210_000;
+pub const INITIAL_EQUILIBRATION_UNITS_V0: u64 = 60 * TX_DATA_NONZERO_GAS_EIP2028 * 100_000;
+pub const INITIAL_EQUILIBRATION_UNITS_V6: u64 = TX_DATA_NONZERO_GAS_EIP2028 * 10_000_000;
+
+/// Nitro's `TxTypeHasPosterCosts`.
+pub fn tx_type_has_poster_costs(tx_type: u8) -> bool {
+    !matches!(
+        ArbTxType::from_u8(tx_type),
+        Ok(ArbTxType::ArbitrumUnsignedTx
+            | ArbTxType::ArbitrumContractTx
+            | ArbTxType::ArbitrumRetryTx
+            | ArbTxType::ArbitrumInternalTx
+            | ArbTxType::ArbitrumSubmitRetryableTx)
+    )
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PosterCost {
+    pub units: u128,
+    pub wei: u128,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchPoster {
+    pub funds_due: U256,
+    pub pay_to: Address,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchPostersTable {
+    pub posters: BTreeMap<Address, BatchPoster>,
+}
+
+impl BatchPostersTable {
+    pub fn add_poster(&mut self, poster: Address, pay_to: Address) {
+        self.posters.entry(poster).or_insert(BatchPoster {
+            funds_due: U256::ZERO,
+            pay_to,
+        });
+    }
+
+    pub fn total_funds_due(&self) -> U256 {
+        self.posters
+            .values()
+            .fold(U256::ZERO, |acc, p| acc.saturating_add(p.funds_due))
+    }
+}
+
+/// ArbOS's L1 pricer state. `l1_base_fee_wei` is `pricePerUnit`, the price
+/// charged per calldata unit, which starts out at the L1 base fee.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct L1PricingState {
     pub l1_base_fee_wei: u128,
+    pub batch_poster_table: BatchPostersTable,
+    pub pay_rewards_to: Address,
+    pub equilibration_units: u128,
+    pub inertia: u64,
+    pub per_unit_reward: u64,
+    pub last_update_time: u64,
+    pub funds_due_for_rewards: U256,
+    pub units_since_update: u64,
+    pub last_surplus: I256,
+    pub per_batch_gas_cost: i64,
+    pub amortized_cost_cap_bips: u64,
+    /// Balance held for paying posters and rewards, `L1FeesAvailable`.
+    pub l1_fees_available: U256,
+}
+
+impl Default for L1PricingState {
+    fn default() -> Self {
+        Self::initial(ArbOsVersion::V12, DEFAULT_INITIAL_L1_BASE_FEE_WEI)
+    }
+}
+
+impl L1PricingState {
+    /// The pricer as a chain starting at `arbos_version` initializes it.
+    pub fn initial(arbos_version: ArbOsVersion, initial_l1_base_fee_wei: u128) -> Self {
+        let equilibration_units = if arbos_version.is_at_least(ArbOsVersion::V6) {
+            INITIAL_EQUILIBRATION_UNITS_V6
+        } else {
+            INITIAL_EQUILIBRATION_UNITS_V0
+        };
+        let per_batch_gas_cost = if arbos_version.is_at_least(ArbOsVersion::V12) {
+            INITIAL_PER_BATCH_GAS_COST_V12
+        } else if arbos_version.is_at_least(ArbOsVersion::V6) {
+            INITIAL_PER_BATCH_GAS_COST_V6
+        } else {
+            0
+        };
+        let mut batch_poster_table = BatchPostersTable::default();
+        batch_poster_table.add_poster(BATCH_POSTER_ADDRESS, BATCH_POSTER_ADDRESS);
+        Self {
+            l1_base_fee_wei: initial_l1_base_fee_wei,
+            batch_poster_table,
+            pay_rewards_to: BATCH_POSTER_ADDRESS,
+            equilibration_units: equilibration_units as u128,
+            inertia: INITIAL_INERTIA,
+            per_unit_reward: INITIAL_PER_UNIT_REWARD,
+            last_update_time: 0,
+            funds_due_for_rewards: U256::ZERO,
+            units_since_update: 0,
+            last_surplus: I256::ZERO,
+            per_batch_gas_cost,
+            amortized_cost_cap_bips: 0,
+            l1_fees_available: U256::ZERO,
+        }
+    }
+}
+
+/// What a single `UpdateForBatchPosterSpending` call did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchPostingUpdate {
+    pub units_allocated: u64,
+    /// Spending credited to the poster after the amortized cost cap.
+    pub wei_spent: U256,
+    pub rewards_paid: U256,
+    pub poster_paid: U256,
+    pub surplus: Option<I256>,
+    pub price_per_unit: u128,
+}
+
+/// Go's `big.Int.Div` rounds towards negative infinity for positive divisors.
+fn floor_div(a: I256, b: I256) -> I256 {
+    let q = a / b;
+    if a.is_negative() && q * b != a {
+        q - I256::ONE
+    } else {
+        q
+    }
+}
+
+fn to_i256(v: U256) -> I256 {
+    I256::try_from(v).unwrap_or(I256::MAX)
 }
 
 impl L1PricingState {
@@ -33,6 +183,218 @@ impl L1PricingState {
     pub fn poster_data_cost(&self, data_gas: u128) -> u128 {
         self.l1_base_fee_wei.saturating_mul(data_gas)
     }
+
+    /// Poster cost of a transaction given its EIP-2718 binary encoding, as
+    /// ArbOS charges it when the batch poster is the sequencer.
+    #[cfg(feature = "brotli")]
+    pub fn poster_cost_for_tx_bytes(&self, tx_bytes: &[u8], brotli_level: u32) -> PosterCost {
+        let Some(&first) = tx_bytes.first() else {
+            return PosterCost::default();
+        };
+        let is_typed = first < 0xc0;
+        if is_typed && !tx_type_has_poster_costs(first) {
+            return PosterCost::default();
+        }
+        let units =
+            Self::poster_units_from_brotli_len(brotli_compressed_len(tx_bytes, brotli_level));
+        PosterCost {
+            units,
+            wei: self.poster_data_cost_from_units(units),
+        }
+    }
+
+    #[cfg(feature = "brotli")]
+    pub fn poster_cost(&self, tx: &ArbTxEnvelope, brotli_level: u32) -> PosterCost {
+        self.poster_cost_for_tx_bytes(&tx.encode_typed(), brotli_level)
+    }
+
+    /// `poster_cost` at the brotli level `arbos_version` defaults to.
+    #[cfg(feature = "brotli")]
+    pub fn poster_cost_at_version(
+        &self,
+        tx: &ArbTxEnvelope,
+        arbos_version: ArbOsVersion,
+    ) -> PosterCost {
+        self.poster_cost(tx, arbos_version.default_brotli_compression_level())
+    }
+
+    /// The padded cost ArbOS uses when it only has a message, as during gas
+    /// estimation.
+    #[cfg(feature = "brotli")]
+    pub fn poster_cost_estimate(&self, tx_bytes: &[u8], brotli_level: u32) -> PosterCost {
+        let (wei, units) =
+            self.poster_data_cost_estimate_from_len(brotli_compressed_len(tx_bytes, brotli_level));
+        PosterCost { units, wei }
+    }
+
+    /// Records a transaction's poster payment of `units` at the current price.
+    pub fn record_poster_payment(&mut self, units: u64) -> U256 {
+        let cost = U256::from(self.l1_base_fee_wei).saturating_mul(U256::from(units));
+        self.units_since_update = self.units_since_update.saturating_add(units);
+        self.l1_fees_available = self.l1_fees_available.saturating_add(cost);
+        cost
+    }
+
+    /// The spending a batch posting report credits to its poster.
+    pub fn batch_posting_wei_spent(&self, batch_data_gas: u64, l1_base_fee: U256) -> U256 {
+        let gas_spent = self
+            .per_batch_gas_cost
+            .saturating_add(i64::try_from(batch_data_gas).unwrap_or(i64::MAX))
+            .max(0) as u64;
+        l1_base_fee.saturating_mul(U256::from(gas_spent))
+    }
+
+    /// ArbOS's `UpdateForBatchPosterSpending` (ArbOS 10 and later).
+    pub fn update_for_batch_poster_spending(
+        &mut self,
+        arbos_version: ArbOsVersion,
+        update_time: u64,
+        current_time: u64,
+        batch_poster: Address,
+        wei_spent: U256,
+        l1_base_fee: U256,
+    ) -> Result<BatchPostingUpdate, L1PricingError> {
+        self.batch_poster_table
+            .add_poster(batch_poster, batch_poster);
+
+        let mut last_update_time = self.last_update_time;
+        if last_update_time == 0 && update_time > 0 {
+            last_update_time = update_time - 1;
+        }
+        if update_time > current_time || update_time < last_update_time {
+            return Err(L1PricingError::InvalidTime);
+        }
+        let (mut numerator, mut denominator) = (
+            update_time - last_update_time,
+            current_time - last_update_time,
+        );
+        if denominator == 0 {
+            numerator = 1;
+            denominator = 1;
+        }
+
+        let units_allocated =
+            ((self.units_since_update as u128 * numerator as u128) / denominator as u128) as u64;
+        self.units_since_update -= units_allocated;
+
+        let mut wei_spent = wei_spent;
+        if arbos_version.is_at_least(ArbOsVersion::V3) && self.amortized_cost_cap_bips != 0 {
+            let cap = l1_base_fee
+                .saturating_mul(U256::from(units_allocated))
+                .saturating_mul(U256::from(self.amortized_cost_cap_bips))
+                / U256::from(ONE_IN_BIPS);
+            wei_spent = wei_spent.min(cap);
+        }
+
+        let reward = U256::from(self.per_unit_reward) * U256::from(units_allocated);
+        self.funds_due_for_rewards = self.funds_due_for_rewards.saturating_add(reward);
+        let rewards_paid = reward.min(self.l1_fees_available);
+        self.funds_due_for_rewards -= rewards_paid.min(self.funds_due_for_rewards);
+        self.l1_fees_available -= rewards_paid;
+
+        let poster = self
+            .batch_poster_table
+            .posters
+            .get_mut(&batch_poster)
+            .expect("poster was just added");
+        poster.funds_due = poster.funds_due.saturating_add(wei_spent);
+        let poster_paid = poster.funds_due.min(self.l1_fees_available);
+        poster.funds_due -= poster_paid;
+        self.l1_fees_available -= poster_paid;
+
+        self.last_update_time = update_time;
+
+        let mut surplus = None;
+        if units_allocated > 0 {
+            let due = self
+                .batch_poster_table
+                .total_funds_due()
+                .saturating_add(self.funds_due_for_rewards);
+            let current = to_i256(self.l1_fees_available) - to_i256(due);
+
+            let units = I256::try_from(units_allocated).expect("u64 fits");
+            let equil_units = I256::try_from(self.equilibration_units).expect("u128 fits");
+            let inertia_units =
+                equil_units / I256::try_from(self.inertia.max(1)).expect("u64 fits");
+            let alloc_plus_inert = inertia_units + units;
+
+            let desired_derivative = floor_div(-current, equil_units);
+            let actual_derivative = floor_div(current - self.last_surplus, units);
+            let change = desired_derivative - actual_derivative;
+            let price_change = floor_div(change * units, alloc_plus_inert);
+
+            self.last_surplus = current;
+            let new_price = I256::try_from(self.l1_base_fee_wei).expect("u128 fits") + price_change;
+            self.l1_base_fee_wei = if new_price.is_negative() {
+                0
+            } else {
+                u128::try_from(new_price).unwrap_or(u128::MAX)
+            };
+            surplus = Some(current);
+        }
+
+        Ok(BatchPostingUpdate {
+            units_allocated,
+            wei_spent,
+            rewards_paid,
+            poster_paid,
+            surplus,
+            price_per_unit: self.l1_base_fee_wei,
+        })
+    }
+
+    pub fn checked_poster_data_cost_from_units(&self, units: u128) -> Result<u128, L1PricingError> {
+        self.l1_base_fee_wei
+            .checked_mul(units)
+            .ok_or(L1PricingError::Overflow)
+    }
+
+    pub fn checked_apply_estimation_padding(units: u128) -> Result<u128, L1PricingError> {
+        let bips = (ONE_IN_BIPS + ESTIMATION_PADDING_BASIS_POINTS) as u128;
+        units
+            .checked_add(ESTIMATION_PADDING_UNITS as u128)
+            .and_then(|padded| padded.checked_mul(bips))
+            .map(|scaled| scaled / ONE_IN_BIPS as u128)
+            .ok_or(L1PricingError::Overflow)
+    }
+
+    pub fn checked_poster_data_cost_estimate_from_len(
+        &self,
+        brotli_len_bytes: u64,
+    ) -> Result<(u128, u128), L1PricingError> {
+        let units = Self::poster_units_from_brotli_len(brotli_len_bytes);
+        let padded_units = Self::checked_apply_estimation_padding(units)?;
+        Ok((
+            self.checked_poster_data_cost_from_units(padded_units)?,
+            padded_units,
+        ))
+    }
+
+    pub fn checked_poster_data_cost_from_units_u256(
+        &self,
+        units: U256,
+    ) -> Result<U256, L1PricingError> {
+        U256::from(self.l1_base_fee_wei)
+            .checked_mul(units)
+            .ok_or(L1PricingError::Overflow)
+    }
+
+    pub fn checked_apply_estimation_padding_u256(units: U256) -> Result<U256, L1PricingError> {
+        let bips = U256::from(ONE_IN_BIPS + ESTIMATION_PADDING_BASIS_POINTS);
+        units
+            .checked_add(U256::from(ESTIMATION_PADDING_UNITS))
+            .and_then(|padded| padded.checked_mul(bips))
+            .map(|scaled| scaled / U256::from(ONE_IN_BIPS))
+            .ok_or(L1PricingError::Overflow)
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum L1PricingError {
+    #[error("L1 pricing arithmetic overflow")]
+    Overflow,
+    #[error("invalid time")]
+    InvalidTime,
 }
 
 #[cfg(test)]
@@ -67,6 +429,7 @@ mod tests {
     fn poster_data_cost_from_units_multiplies_by_price_per_unit() {
         let state = L1PricingState {
             l1_base_fee_wei: 1_000,
+            ..Default::default()
         };
         assert_eq!(state.poster_data_cost_from_units(0), 0);
         assert_eq!(state.poster_data_cost_from_units(1), 1_000);
@@ -77,6 +440,7 @@ mod tests {
     fn poster_data_cost_estimate_from_len_pipeline() {
         let state = L1PricingState {
             l1_base_fee_wei: 1_000,
+            ..Default::default()
         };
         let len = 100u64;
         let (cost, padded_units) = state.poster_data_cost_estimate_from_len(len);
@@ -90,13 +454,273 @@ mod tests {
     fn poster_data_cost_multiplies_base_fee_by_data_gas() {
         let state = L1PricingState {
             l1_base_fee_wei: 1_000,
+            ..Default::default()
         };
         assert_eq!(state.poster_data_cost(123456789), 123_456_789_000);
     }
 
+    #[test]
+    fn checked_variants_agree_with_saturating_ones_in_range() {
+        let state = L1PricingState {
+            l1_base_fee_wei: 1_000,
+            ..Default::default()
+        };
+        assert_eq!(
+            state.checked_poster_data_cost_estimate_from_len(100),
+            Ok(state.poster_data_cost_estimate_from_len(100))
+        );
+        assert_eq!(
+            L1PricingState::checked_apply_estimation_padding_u256(U256::from(10_000u64)),
+            Ok(U256::from(L1PricingState::apply_estimation_padding(10_000)))
+        );
+        assert_eq!(
+            state.checked_poster_data_cost_from_units_u256(U256::from(7u64)),
+            Ok(U256::from(7_000u64))
+        );
+    }
+
+    #[test]
+    fn checked_variants_report_overflow() {
+        let state = L1PricingState {
+            l1_base_fee_wei: u128::MAX,
+            ..Default::default()
+        };
+        assert_eq!(
+            state.checked_poster_data_cost_from_units(2),
+            Err(L1PricingError::Overflow)
+        );
+        assert_eq!(
+            L1PricingState::checked_apply_estimation_padding(u128::MAX / 2),
+            Err(L1PricingError::Overflow)
+        );
+        assert_eq!(
+            state.checked_poster_data_cost_from_units_u256(U256::MAX),
+            Err(L1PricingError::Overflow)
+        );
+        assert_eq!(
+            state.checked_poster_data_cost_from_units_u256(U256::from(2u64)),
+            Ok(U256::from(u128::MAX) * U256::from(2u64))
+        );
+        assert_eq!(
+            L1PricingState::checked_apply_estimation_padding_u256(U256::MAX),
+            Err(L1PricingError::Overflow)
+        );
+    }
+
+    #[test]
+    fn default_state_matches_arbos_initialization() {
+        let state = L1PricingState::default();
+        assert_eq!(state.l1_base_fee_wei, 50_000_000_000);
+        assert_eq!(state.equilibration_units, 160_000_000);
+        assert_eq!(INITIAL_EQUILIBRATION_UNITS_V0, 96_000_000);
+        assert_eq!(state.inertia, 10);
+        assert_eq!(state.per_unit_reward, 10);
+        assert!(state
+            .batch_poster_table
+            .posters
+            .contains_key(&BATCH_POSTER_ADDRESS));
+    }
+
+    #[test]
+    fn initial_state_follows_arbos_version() {
+        let v0 = L1PricingState::initial(ArbOsVersion::V1, 1);
+        assert_eq!(
+            v0.equilibration_units,
+            INITIAL_EQUILIBRATION_UNITS_V0 as u128
+        );
+        assert_eq!(v0.per_batch_gas_cost, 0);
+        let v6 = L1PricingState::initial(ArbOsVersion::V6, 1);
+        assert_eq!(v6.per_batch_gas_cost, INITIAL_PER_BATCH_GAS_COST_V6);
+        let v12 = L1PricingState::initial(ArbOsVersion::V32, 1);
+        assert_eq!(v12.per_batch_gas_cost, INITIAL_PER_BATCH_GAS_COST_V12);
+        assert_eq!(
+            v12.equilibration_units,
+            INITIAL_EQUILIBRATION_UNITS_V6 as u128
+        );
+    }
+
+    #[test]
+    fn amortized_cost_cap_needs_arbos_3() {
+        let mut state = L1PricingState {
+            l1_base_fee_wei: 100,
+            amortized_cost_cap_bips: 5_000,
+            ..Default::default()
+        };
+        state.last_update_time = 1;
+        state.record_poster_payment(1_000);
+        let upd = state
+            .update_for_batch_poster_spending(
+                ArbOsVersion::V2,
+                2,
+                2,
+                BATCH_POSTER_ADDRESS,
+                U256::from(1_000_000u64),
+                U256::from(100u64),
+            )
+            .unwrap();
+        assert_eq!(upd.wei_spent, U256::from(1_000_000u64));
+    }
+
+    #[test]
+    fn batch_posting_pays_rewards_and_poster_from_available_fees() {
+        let mut state = L1PricingState {
+            l1_base_fee_wei: 100,
+            ..Default::default()
+        };
+        state.last_update_time = 1_000;
+        assert_eq!(
+            state.record_poster_payment(10_000),
+            U256::from(1_000_000u64)
+        );
+
+        let spent = state.batch_posting_wei_spent(0, U256::from(5u64));
+        assert_eq!(spent, U256::from(5u64 * 210_000));
+        let upd = state
+            .update_for_batch_poster_spending(
+                ArbOsVersion::V20,
+                1_010,
+                1_010,
+                BATCH_POSTER_ADDRESS,
+                U256::from(500_000u64),
+                U256::from(100u64),
+            )
+            .unwrap();
+        assert_eq!(upd.units_allocated, 10_000);
+        assert_eq!(upd.rewards_paid, U256::from(100_000u64));
+        assert_eq!(upd.poster_paid, U256::from(500_000u64));
+        assert_eq!(state.l1_fees_available, U256::from(400_000u64));
+        assert_eq!(state.units_since_update, 0);
+        assert_eq!(state.last_update_time, 1_010);
+        // A surplus pushes the price down.
+        assert_eq!(upd.surplus, Some(I256::try_from(400_000).unwrap()));
+        assert!(upd.price_per_unit < 100);
+    }
+
+    #[test]
+    fn batch_posting_shortfall_raises_price_and_allocates_by_time() {
+        let mut state = L1PricingState {
+            l1_base_fee_wei: 100,
+            equilibration_units: 100_000,
+            ..Default::default()
+        };
+        state.last_update_time = 1_000;
+        state.record_poster_payment(10_000);
+        let upd = state
+            .update_for_batch_poster_spending(
+                ArbOsVersion::V20,
+                1_005,
+                1_010,
+                BATCH_POSTER_ADDRESS,
+                U256::from(5_000_000u64),
+                U256::from(100u64),
+            )
+            .unwrap();
+        assert_eq!(upd.units_allocated, 5_000);
+        assert_eq!(state.units_since_update, 5_000);
+        assert!(upd.surplus.unwrap().is_negative());
+        assert_eq!(upd.price_per_unit, 100 + 283);
+        let owed = state.batch_poster_table.total_funds_due();
+        assert_eq!(owed, U256::from(5_000_000u64) - upd.poster_paid);
+
+        assert_eq!(
+            state.update_for_batch_poster_spending(
+                ArbOsVersion::V20,
+                1_004,
+                1_010,
+                BATCH_POSTER_ADDRESS,
+                U256::ZERO,
+                U256::ZERO,
+            ),
+            Err(L1PricingError::InvalidTime)
+        );
+    }
+
+    #[test]
+    fn amortized_cost_cap_limits_credited_spending() {
+        let mut state = L1PricingState {
+            l1_base_fee_wei: 100,
+            amortized_cost_cap_bips: 5_000,
+            ..Default::default()
+        };
+        state.last_update_time = 1;
+        state.record_poster_payment(1_000);
+        let upd = state
+            .update_for_batch_poster_spending(
+                ArbOsVersion::V20,
+                2,
+                2,
+                BATCH_POSTER_ADDRESS,
+                U256::from(1_000_000u64),
+                U256::from(100u64),
+            )
+            .unwrap();
+        assert_eq!(upd.wei_spent, U256::from(50_000u64));
+    }
+
+    #[test]
+    fn floor_div_rounds_like_go_big_int() {
+        let n = |v: i64| I256::try_from(v).unwrap();
+        assert_eq!(floor_div(n(7), n(2)), n(3));
+        assert_eq!(floor_div(n(-7), n(2)), n(-4));
+        assert_eq!(floor_div(n(-8), n(2)), n(-4));
+    }
+
+    #[test]
+    fn poster_costs_apply_to_user_tx_types_only() {
+        assert!(tx_type_has_poster_costs(0x02));
+        assert!(tx_type_has_poster_costs(0x00));
+        assert!(!tx_type_has_poster_costs(0x65));
+        assert!(!tx_type_has_poster_costs(0x68));
+        assert!(!tx_type_has_poster_costs(0x69));
+        assert!(!tx_type_has_poster_costs(0x6a));
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn poster_cost_compresses_tx_bytes() {
+        let state = L1PricingState {
+            l1_base_fee_wei: 1_000,
+            ..Default::default()
+        };
+        let mut tx = alloc::vec![0x02u8];
+        tx.extend(core::iter::repeat_n(0xabu8, 512));
+        let len = brotli_compressed_len(&tx, 1);
+        assert!(len > 0 && len < 64);
+
+        let cost = state.poster_cost_for_tx_bytes(&tx, 1);
+        assert_eq!(cost.units, len as u128 * 16);
+        assert_eq!(cost.wei, cost.units * 1_000);
+
+        let est = state.poster_cost_estimate(&tx, 1);
+        assert_eq!(
+            est.units,
+            L1PricingState::apply_estimation_padding(cost.units)
+        );
+        assert_eq!(
+            state.poster_cost_for_tx_bytes(&[], 1),
+            PosterCost::default()
+        );
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn poster_cost_is_zero_for_arbitrum_system_txs() {
+        use alloy_primitives::Bytes;
+        use arb_alloy_consensus::tx::ArbInternalTx;
+        let state = L1PricingState::default();
+        let tx = ArbTxEnvelope::Internal(ArbInternalTx {
+            chain_id: U256::from(42161u64),
+            data: Bytes::from_static(&[1, 2, 3]),
+        });
+        assert_eq!(state.poster_cost(&tx, 1), PosterCost::default());
+    }
+
     #[test]
     fn poster_data_cost_is_zero_when_base_fee_zero() {
-        let state = L1PricingState { l1_base_fee_wei: 0 };
+        let state = L1PricingState {
+            l1_base_fee_wei: 0,
+            ..Default::default()
+        };
         assert_eq!(state.poster_data_cost_from_units(123456), 0);
         assert_eq!(state.poster_data_cost(987654321), 0);
     }
@@ -137,7 +761,10 @@ mod tests {
 
     #[test]
     fn data_cost_linear_in_basefee() {
-        let state_zero = L1PricingState { l1_base_fee_wei: 0 };
+        let state_zero = L1PricingState {
+            l1_base_fee_wei: 0,
+            ..Default::default()
+        };
         assert_eq!(state_zero.poster_data_cost_from_units(0), 0);
         assert_eq!(state_zero.poster_data_cost_from_units(12345), 0);
 
@@ -147,6 +774,7 @@ mod tests {
             for fee in fees {
                 let st = L1PricingState {
                     l1_base_fee_wei: fee,
+                    ..Default::default()
                 };
                 assert_eq!(
                     st.poster_data_cost_from_units(units),
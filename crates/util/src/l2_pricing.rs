@@ -0,0 +1,222 @@
+#![allow(dead_code)]
+
+use crate::l1_pricing::ONE_IN_BIPS;
+use arb_alloy_consensus::ArbOsVersion;
+
+pub const INITIAL_SPEED_LIMIT_PER_SECOND_V0: u64 = 1_000_000;
+pub const INITIAL_PER_BLOCK_GAS_LIMIT_V0: u64 = 20_000_000;
+pub const INITIAL_SPEED_LIMIT_PER_SECOND_V6: u64 = 7_000_000;
+pub const INITIAL_PER_BLOCK_GAS_LIMIT_V6: u64 = 32_000_000;
+pub const INITIAL_PER_TX_GAS_LIMIT: u64 = 32_000_000;
+pub const INITIAL_MINIMUM_BASE_FEE_WEI: u128 = 100_000_000;
+pub const INITIAL_BASE_FEE_WEI: u128 = INITIAL_MINIMUM_BASE_FEE_WEI;
+pub const INITIAL_PRICING_INERTIA: u64 = 102;
+pub const INITIAL_BACKLOG_TOLERANCE: u64 = 10;
+
+/// Nitro's `ApproxExpBasisPoints`: a Maclaurin approximation of `e^x` with `x`
+/// and the result in basis points.
+pub fn approx_exp_basis_points(value_bips: i64, accuracy: u64) -> i64 {
+    let negative = value_bips < 0;
+    let x = value_bips.unsigned_abs();
+    let bips = ONE_IN_BIPS;
+
+    let mut res = bips + x / accuracy;
+    for i in 1..accuracy {
+        res = bips + res.saturating_mul(x) / ((accuracy - i) * bips);
+    }
+
+    let res = if negative { bips * bips / res } else { res };
+    i64::try_from(res).unwrap_or(i64::MAX)
+}
+
+/// ArbOS's L2 pricing state: a gas backlog drained at the speed limit, with
+/// the base fee growing exponentially in the backlog beyond the tolerance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct L2PricingState {
+    pub speed_limit_per_second: u64,
+    pub per_block_gas_limit: u64,
+    pub per_tx_gas_limit: u64,
+    pub base_fee_wei: u128,
+    pub min_base_fee_wei: u128,
+    pub gas_backlog: u64,
+    pub pricing_inertia: u64,
+    pub backlog_tolerance: u64,
+}
+
+impl Default for L2PricingState {
+    fn default() -> Self {
+        Self::initial(ArbOsVersion::V6)
+    }
+}
+
+impl L2PricingState {
+    /// The pricing state as a chain starting at `arbos_version` initializes it.
+    pub fn initial(arbos_version: ArbOsVersion) -> Self {
+        let (speed_limit_per_second, per_block_gas_limit) =
+            if arbos_version.is_at_least(ArbOsVersion::V6) {
+                (
+                    INITIAL_SPEED_LIMIT_PER_SECOND_V6,
+                    INITIAL_PER_BLOCK_GAS_LIMIT_V6,
+                )
+            } else {
+                (
+                    INITIAL_SPEED_LIMIT_PER_SECOND_V0,
+                    INITIAL_PER_BLOCK_GAS_LIMIT_V0,
+                )
+            };
+        Self {
+            speed_limit_per_second,
+            per_block_gas_limit,
+            per_tx_gas_limit: INITIAL_PER_TX_GAS_LIMIT,
+            base_fee_wei: INITIAL_BASE_FEE_WEI,
+            min_base_fee_wei: INITIAL_MINIMUM_BASE_FEE_WEI,
+            gas_backlog: 0,
+            pricing_inertia: INITIAL_PRICING_INERTIA,
+            backlog_tolerance: INITIAL_BACKLOG_TOLERANCE,
+        }
+    }
+
+    /// The most gas a single transaction may use. Before ArbOS 50 the block
+    /// gas limit applied to each transaction.
+    pub fn max_tx_gas_limit(&self, arbos_version: ArbOsVersion) -> u64 {
+        if arbos_version.has_per_tx_gas_limit() {
+            self.per_tx_gas_limit
+        } else {
+            self.per_block_gas_limit
+        }
+    }
+
+    /// Positive `gas` drains the backlog, negative `gas` adds to it.
+    pub fn add_to_gas_pool(&mut self, gas: i64) {
+        self.gas_backlog = if gas > 0 {
+            self.gas_backlog.saturating_sub(gas as u64)
+        } else {
+            self.gas_backlog.saturating_add(gas.unsigned_abs())
+        };
+    }
+
+    pub fn record_gas_used(&mut self, gas_used: u64) {
+        self.add_to_gas_pool(-i64::try_from(gas_used).unwrap_or(i64::MAX));
+    }
+
+    /// ArbOS's `UpdatePricingModel`, run at the start of every block.
+    pub fn update_pricing_model(&mut self, time_passed: u64) {
+        let speed_limit = self.speed_limit_per_second;
+        let refill = time_passed.saturating_mul(speed_limit);
+        self.add_to_gas_pool(i64::try_from(refill).unwrap_or(i64::MAX));
+
+        let tolerated = self.backlog_tolerance.saturating_mul(speed_limit);
+        let mut base_fee = self.min_base_fee_wei;
+        if self.gas_backlog > tolerated {
+            let excess = i64::try_from(self.gas_backlog - tolerated).unwrap_or(i64::MAX);
+            let divisor = i64::try_from(self.pricing_inertia.saturating_mul(speed_limit))
+                .unwrap_or(i64::MAX)
+                .max(1);
+            let exponent_bips = excess.saturating_mul(ONE_IN_BIPS as i64) / divisor;
+            let multiplier = approx_exp_basis_points(exponent_bips, 4) as u128;
+            base_fee = self.min_base_fee_wei.saturating_mul(multiplier) / ONE_IN_BIPS as u128;
+        }
+        self.base_fee_wei = base_fee;
+    }
+
+    /// Produces one block: the header takes the current base fee, the start
+    /// of block update runs, then the block's gas joins the backlog. Returns
+    /// the block's base fee.
+    pub fn apply_block(&mut self, time_passed: u64, gas_used: u64) -> u128 {
+        let base_fee = self.base_fee_wei;
+        self.update_pricing_model(time_passed);
+        self.record_gas_used(gas_used);
+        base_fee
+    }
+
+    /// Runs `apply_block` over `(time_passed, gas_used)` pairs and collects the
+    /// base fee of every block.
+    pub fn simulate<I>(&mut self, blocks: I) -> alloc::vec::Vec<u128>
+    where
+        I: IntoIterator<Item = (u64, u64)>,
+    {
+        blocks
+            .into_iter()
+            .map(|(time_passed, gas_used)| self.apply_block(time_passed, gas_used))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approx_exp_matches_nitro_values() {
+        assert_eq!(approx_exp_basis_points(0, 4), 10_000);
+        assert_eq!(approx_exp_basis_points(10_000, 4), 27_083);
+        assert_eq!(approx_exp_basis_points(-10_000, 4), 3_692);
+        assert!(approx_exp_basis_points(20_000, 4) < 73_891);
+    }
+
+    #[test]
+    fn initial_state_matches_arbos() {
+        let st = L2PricingState::default();
+        assert_eq!(st.speed_limit_per_second, 7_000_000);
+        assert_eq!(st.per_block_gas_limit, 32_000_000);
+        assert_eq!(st.min_base_fee_wei, 100_000_000);
+        assert_eq!(st.pricing_inertia, 102);
+        assert_eq!(st.backlog_tolerance, 10);
+    }
+
+    #[test]
+    fn initial_state_follows_arbos_version() {
+        let v1 = L2PricingState::initial(ArbOsVersion::V1);
+        assert_eq!(v1.speed_limit_per_second, INITIAL_SPEED_LIMIT_PER_SECOND_V0);
+        assert_eq!(v1.per_block_gas_limit, INITIAL_PER_BLOCK_GAS_LIMIT_V0);
+        assert_eq!(v1.max_tx_gas_limit(ArbOsVersion::V1), 20_000_000);
+
+        let mut st = L2PricingState::initial(ArbOsVersion::V50);
+        st.per_tx_gas_limit = 1_000_000;
+        assert_eq!(st.max_tx_gas_limit(ArbOsVersion::V40), 32_000_000);
+        assert_eq!(st.max_tx_gas_limit(ArbOsVersion::V50), 1_000_000);
+    }
+
+    #[test]
+    fn backlog_saturates_in_both_directions() {
+        let mut st = L2PricingState::default();
+        st.add_to_gas_pool(5);
+        assert_eq!(st.gas_backlog, 0);
+        st.record_gas_used(u64::MAX);
+        assert_eq!(st.gas_backlog, i64::MAX as u64);
+        st.add_to_gas_pool(i64::MAX);
+        assert_eq!(st.gas_backlog, 0);
+    }
+
+    #[test]
+    fn base_fee_stays_at_minimum_within_tolerance() {
+        let mut st = L2PricingState::default();
+        st.gas_backlog = st.backlog_tolerance * st.speed_limit_per_second;
+        st.update_pricing_model(0);
+        assert_eq!(st.base_fee_wei, st.min_base_fee_wei);
+    }
+
+    #[test]
+    fn base_fee_rises_under_load_and_decays_after() {
+        let mut st = L2PricingState::default();
+        let speed = st.speed_limit_per_second;
+        let loaded = st.simulate((0..200).map(|_| (1, 3 * speed)));
+        assert_eq!(loaded[0], INITIAL_BASE_FEE_WEI);
+        assert!(loaded.windows(2).all(|w| w[1] >= w[0]));
+        let peak = *loaded.last().unwrap();
+        assert!(peak > st.min_base_fee_wei);
+
+        let idle = st.simulate((0..600).map(|_| (1, 0)));
+        assert!(idle[1..].windows(2).all(|w| w[1] <= w[0]));
+        assert_eq!(*idle.last().unwrap(), st.min_base_fee_wei);
+    }
+
+    #[test]
+    fn base_fee_matches_exponential_formula() {
+        let mut st = L2PricingState::default();
+        let speed = st.speed_limit_per_second;
+        st.gas_backlog = st.backlog_tolerance * speed + st.pricing_inertia * speed;
+        st.update_pricing_model(0);
+        assert_eq!(st.base_fee_wei, st.min_base_fee_wei * 27_083 / 10_000);
+    }
+}
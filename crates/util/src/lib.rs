@@ -2,8 +2,28 @@
 #![allow(dead_code)]
 
 extern crate alloc;
+#[cfg(feature = "brotli")]
+extern crate std;
 
+pub mod arbos_storage;
+pub mod blobs;
+pub mod chain;
+#[cfg(feature = "brotli")]
+pub mod compression;
+pub mod das;
+pub mod fees;
+pub mod gas;
+pub mod inbox;
 pub mod l1_pricing;
+pub mod l2_pricing;
+pub mod node_interface;
+pub mod outbox;
 pub mod retryables;
+pub mod sequencer_batch;
+pub mod stylus;
+pub mod stylus_code;
 
-pub use retryables::{escrow_address_from_ticket, retryable_submission_fee};
+pub use retryables::{
+    escrow_address_from_ticket, retry_tx_refunds, retryable_submission_fee,
+    submit_retryable_breakdown,
+};
@@ -0,0 +1,376 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{Encodable, Header};
+use arb_alloy_consensus::tx::ArbSubmitRetryableTx;
+use arb_alloy_predeploys::l1_contracts::apply_l1_to_l2_alias;
+
+use crate::l1_pricing::L1PricingState;
+#[cfg(feature = "brotli")]
+use crate::l1_pricing::ONE_IN_BIPS;
+use crate::retryables::{checked_retryable_submission_fee_u256, RetryableError};
+
+/// ArbOS pads the L1 component of gas estimates by 10%.
+pub const GAS_ESTIMATION_L1_PRICE_PADDING_BIPS: u64 = 11_000;
+
+const DYNAMIC_FEE_TX_TYPE: u8 = 0x02;
+
+/// Nitro's `randomNonce`, used when a message has no nonce.
+pub fn random_nonce() -> u64 {
+    let hash = keccak256(b"Nonce");
+    u64::from_be_bytes(hash[..8].try_into().unwrap())
+}
+
+/// Nitro's `randomGasTipCap`, used when a message has no tip cap.
+pub fn random_gas_tip_cap() -> U256 {
+    U256::from_be_slice(&keccak256(b"GasTipCap")[..4])
+}
+
+/// Nitro's `randomGasFeeCap`, used when a message has no fee cap.
+pub fn random_gas_fee_cap() -> U256 {
+    U256::from_be_slice(&keccak256(b"GasFeeCap")[..4])
+}
+
+/// Nitro's `RandomGas`, the gas limit of every message during estimation.
+pub fn random_gas() -> u64 {
+    let hash = keccak256(b"Gas");
+    u64::from(u32::from_be_bytes(hash[..4].try_into().unwrap()))
+}
+
+/// The parts of a call NodeInterface prices for L1. Zero nonce and fee caps
+/// stand for unset ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EstimationMessage {
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+    pub nonce: u64,
+    pub gas_tip_cap: U256,
+    pub gas_fee_cap: U256,
+}
+
+impl EstimationMessage {
+    pub fn new(to: Option<Address>, value: U256, data: Bytes) -> Self {
+        Self {
+            to,
+            value,
+            data,
+            ..Default::default()
+        }
+    }
+
+    /// The binary encoding of `makeFakeTxForMessage`'s unsigned dynamic fee
+    /// tx, whose compressed size sets the L1 cost of the message.
+    pub fn fake_tx_bytes(&self) -> Vec<u8> {
+        let nonce = match self.nonce {
+            0 => random_nonce(),
+            n => n,
+        };
+        let or_random = |v: U256, random: fn() -> U256| if v.is_zero() { random() } else { v };
+        let gas_tip_cap = or_random(self.gas_tip_cap, random_gas_tip_cap);
+        let gas_fee_cap = or_random(self.gas_fee_cap, random_gas_fee_cap);
+
+        let mut payload = Vec::new();
+        U256::ZERO.encode(&mut payload);
+        nonce.encode(&mut payload);
+        gas_tip_cap.encode(&mut payload);
+        gas_fee_cap.encode(&mut payload);
+        random_gas().encode(&mut payload);
+        match self.to {
+            Some(to) => to.encode(&mut payload),
+            None => payload.push(alloy_rlp::EMPTY_STRING_CODE),
+        }
+        self.value.encode(&mut payload);
+        self.data.encode(&mut payload);
+        payload.push(alloy_rlp::EMPTY_LIST_CODE);
+        for _ in 0..3 {
+            U256::ZERO.encode(&mut payload);
+        }
+
+        let mut out = alloc::vec![DYNAMIC_FEE_TX_TYPE];
+        Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut out);
+        out.extend_from_slice(&payload);
+        out
+    }
+}
+
+/// What `NodeInterface.gasEstimateComponents` returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasEstimateComponents {
+    pub gas_estimate: u64,
+    pub gas_estimate_for_l1: u64,
+    pub base_fee: U256,
+    pub l1_base_fee_estimate: U256,
+}
+
+/// The poster cost of `msg` at the chain's brotli compression level, padded
+/// by `GAS_ESTIMATION_L1_PRICE_PADDING_BIPS`.
+#[cfg(feature = "brotli")]
+fn padded_l1_fees(msg: &EstimationMessage, pricing: &L1PricingState, brotli_level: u32) -> U256 {
+    U256::from(
+        pricing
+            .poster_cost_estimate(&msg.fake_tx_bytes(), brotli_level)
+            .wei,
+    )
+    .saturating_mul(U256::from(GAS_ESTIMATION_L1_PRICE_PADDING_BIPS))
+        / U256::from(ONE_IN_BIPS)
+}
+
+/// ArbOS's `GetPosterGas` in gas estimation mode: the poster cost in gas at
+/// 7/8 of the base fee, but no less than the minimum base fee, so the
+/// estimate leaves room for the base fee to rise.
+pub fn estimation_poster_gas(poster_cost: U256, base_fee: U256, min_base_fee: U256) -> u64 {
+    let adjusted_price = (base_fee.saturating_mul(U256::from(7)) / U256::from(8)).max(min_base_fee);
+    poster_cost
+        .checked_div(adjusted_price)
+        .map_or(0, |gas| gas.saturating_to::<u64>())
+}
+
+/// `NodeInterface.gasEstimateL1Component`: the padded L1 fee in L2 gas at
+/// `base_fee`, the base fee and the L1 price per unit. `brotli_level` is the
+/// chain's stored compression level.
+#[cfg(feature = "brotli")]
+pub fn gas_estimate_l1_component(
+    msg: &EstimationMessage,
+    pricing: &L1PricingState,
+    base_fee: U256,
+    brotli_level: u32,
+) -> (u64, U256, U256) {
+    let gas_for_l1 = padded_l1_fees(msg, pricing, brotli_level)
+        .checked_div(base_fee)
+        .map_or(0, |gas| gas.saturating_to::<u64>());
+    (gas_for_l1, base_fee, U256::from(pricing.l1_base_fee_wei))
+}
+
+/// `NodeInterface.gasEstimateComponents`. `l2_execution_gas` is what the call
+/// needs excluding L1 costs; the estimate adds the L1 component to it, as
+/// `eth_estimateGas` does on Arbitrum. Unlike `gas_estimate_l1_component`,
+/// the L1 component is priced with `estimation_poster_gas`.
+#[cfg(feature = "brotli")]
+pub fn gas_estimate_components(
+    msg: &EstimationMessage,
+    pricing: &L1PricingState,
+    base_fee: U256,
+    min_base_fee: U256,
+    brotli_level: u32,
+    l2_execution_gas: u64,
+) -> GasEstimateComponents {
+    let fees_for_l1 = padded_l1_fees(msg, pricing, brotli_level);
+    let gas_estimate_for_l1 = estimation_poster_gas(fees_for_l1, base_fee, min_base_fee);
+    GasEstimateComponents {
+        gas_estimate: l2_execution_gas.saturating_add(gas_estimate_for_l1),
+        gas_estimate_for_l1,
+        base_fee,
+        l1_base_fee_estimate: U256::from(pricing.l1_base_fee_wei),
+    }
+}
+
+/// The arguments of `NodeInterface.estimateRetryableTicket`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetryableTicketEstimate {
+    /// The L1 sender, before aliasing.
+    pub sender: Address,
+    pub deposit: U256,
+    /// The zero address makes the retry a contract creation.
+    pub to: Address,
+    pub l2_call_value: U256,
+    pub excess_fee_refund_address: Address,
+    pub call_value_refund_address: Address,
+    pub data: Bytes,
+}
+
+impl RetryableTicketEstimate {
+    /// The ticket NodeInterface estimates, with the minimum submission fee at
+    /// the current L1 price per unit and the given gas.
+    pub fn submit_retryable_tx(
+        &self,
+        chain_id: U256,
+        pricing: &L1PricingState,
+        base_fee: U256,
+        gas: u64,
+    ) -> Result<ArbSubmitRetryableTx, RetryableError> {
+        let l1_base_fee = U256::from(pricing.l1_base_fee_wei);
+        Ok(ArbSubmitRetryableTx {
+            chain_id,
+            request_id: B256::ZERO,
+            from: apply_l1_to_l2_alias(self.sender),
+            l1_base_fee,
+            deposit_value: self.deposit,
+            gas_fee_cap: base_fee,
+            gas,
+            retry_to: (self.to != Address::ZERO).then_some(self.to),
+            retry_value: self.l2_call_value,
+            beneficiary: self.call_value_refund_address,
+            max_submission_fee: checked_retryable_submission_fee_u256(
+                self.data.len(),
+                l1_base_fee,
+            )?,
+            fee_refund_addr: self.excess_fee_refund_address,
+            retry_data: self.data.clone(),
+        })
+    }
+
+    /// Builds the ticket and fills in its gas from `estimate_gas`, which sees
+    /// the ticket with zero gas.
+    pub fn estimate<F>(
+        &self,
+        chain_id: U256,
+        pricing: &L1PricingState,
+        base_fee: U256,
+        estimate_gas: F,
+    ) -> Result<ArbSubmitRetryableTx, RetryableError>
+    where
+        F: FnOnce(&ArbSubmitRetryableTx) -> u64,
+    {
+        let mut tx = self.submit_retryable_tx(chain_id, pricing, base_fee, 0)?;
+        tx.gas = estimate_gas(&tx);
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    #[cfg(feature = "brotli")]
+    use arb_alloy_consensus::ArbOsVersion;
+
+    #[test]
+    fn random_fields_come_from_keccak() {
+        assert_eq!(random_nonce().to_be_bytes(), keccak256(b"Nonce")[..8]);
+        assert!(random_gas() <= u64::from(u32::MAX));
+        assert!(random_gas_fee_cap() < U256::from(1u64 << 32));
+        assert_ne!(random_gas_tip_cap(), random_gas_fee_cap());
+    }
+
+    #[test]
+    fn fake_tx_fills_unset_fields() {
+        let to = Address::with_last_byte(0x42);
+        let msg = EstimationMessage::new(Some(to), U256::from(1), Bytes::from_static(&[1, 2]));
+        let bytes = msg.fake_tx_bytes();
+        assert_eq!(bytes[0], DYNAMIC_FEE_TX_TYPE);
+        let mut body = &bytes[1..];
+        let header = Header::decode(&mut body).unwrap();
+        assert!(header.list);
+        assert_eq!(header.payload_length, body.len());
+        // Unsigned: chain id and signature values are empty strings.
+        assert_eq!(body[0], alloy_rlp::EMPTY_STRING_CODE);
+        assert!(body.ends_with(&[alloy_rlp::EMPTY_LIST_CODE, 0x80, 0x80, 0x80]));
+
+        let explicit = EstimationMessage {
+            nonce: random_nonce(),
+            gas_tip_cap: random_gas_tip_cap(),
+            gas_fee_cap: random_gas_fee_cap(),
+            ..msg.clone()
+        };
+        assert_eq!(explicit.fake_tx_bytes(), bytes);
+        let create = EstimationMessage { to: None, ..msg };
+        assert_eq!(create.fake_tx_bytes().len(), bytes.len() - 20);
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn components_pad_poster_cost_and_convert_to_gas() {
+        let pricing = L1PricingState::initial(ArbOsVersion::V20, 30_000_000_000);
+        let msg = EstimationMessage::new(
+            Some(Address::with_last_byte(1)),
+            U256::ZERO,
+            Bytes::from_static(&[0xaa; 100]),
+        );
+        let base_fee = U256::from(200_000_000u64);
+        let min_base_fee = U256::from(100_000_000u64);
+        let level = ArbOsVersion::V20.default_brotli_compression_level();
+        let cost = pricing.poster_cost_estimate(&msg.fake_tx_bytes(), level);
+        let padded = U256::from(cost.wei) * U256::from(11_000u64) / U256::from(10_000u64);
+
+        // Components price L1 gas at 7/8 of the base fee.
+        let components =
+            gas_estimate_components(&msg, &pricing, base_fee, min_base_fee, level, 21_000);
+        assert_eq!(
+            U256::from(components.gas_estimate_for_l1),
+            padded / U256::from(175_000_000u64)
+        );
+        assert_eq!(
+            components.gas_estimate,
+            21_000 + components.gas_estimate_for_l1
+        );
+        assert_eq!(components.base_fee, base_fee);
+        assert_eq!(
+            components.l1_base_fee_estimate,
+            U256::from(30_000_000_000u64)
+        );
+
+        let (gas, _, _) = gas_estimate_l1_component(&msg, &pricing, base_fee, level);
+        assert_eq!(U256::from(gas), padded / base_fee);
+        let (gas, _, _) = gas_estimate_l1_component(&msg, &pricing, U256::ZERO, level);
+        assert_eq!(gas, 0);
+
+        // A chain owner's higher compression level shrinks the estimate.
+        let (tighter, _, _) = gas_estimate_l1_component(&msg, &pricing, base_fee, 11);
+        assert!(tighter < U256::from(padded / base_fee).saturating_to::<u64>());
+    }
+
+    #[test]
+    fn estimation_poster_gas_assumes_congestion_above_the_floor() {
+        let cost = U256::from(7_000_000_000u64);
+        let min = U256::from(100_000_000u64);
+        assert_eq!(
+            estimation_poster_gas(cost, U256::from(800_000_000u64), min),
+            10
+        );
+        // 7/8 of a base fee at the minimum would undercut it.
+        assert_eq!(estimation_poster_gas(cost, min, min), 70);
+        assert_eq!(estimation_poster_gas(cost, U256::ZERO, U256::ZERO), 0);
+    }
+
+    #[test]
+    fn retryable_estimate_aliases_sender_and_prices_submission() {
+        let pricing = L1PricingState {
+            l1_base_fee_wei: 20_000_000_000,
+            ..Default::default()
+        };
+        let args = RetryableTicketEstimate {
+            sender: Address::with_last_byte(0x01),
+            deposit: U256::from(10u64).pow(U256::from(18)),
+            to: Address::with_last_byte(0x02),
+            l2_call_value: U256::from(5),
+            excess_fee_refund_address: Address::with_last_byte(0x03),
+            call_value_refund_address: Address::with_last_byte(0x04),
+            data: Bytes::from_static(&[0u8; 100]),
+        };
+        let tx = args
+            .estimate(U256::from(42161), &pricing, U256::from(10_000_000), |tx| {
+                assert_eq!(tx.gas, 0);
+                tx.retry_data.len() as u64 * 16 + 21_000
+            })
+            .unwrap();
+        assert_eq!(
+            tx.from,
+            address!("1111000000000000000000000000000000001112")
+        );
+        assert_eq!(tx.gas, 22_600);
+        assert_eq!(tx.retry_to, Some(args.to));
+        assert_eq!(tx.beneficiary, args.call_value_refund_address);
+        assert_eq!(tx.fee_refund_addr, args.excess_fee_refund_address);
+        assert_eq!(tx.gas_fee_cap, U256::from(10_000_000));
+        assert_eq!(tx.l1_base_fee, U256::from(20_000_000_000u64));
+        assert_eq!(
+            tx.max_submission_fee,
+            U256::from((1400 + 6 * 100) * 20_000_000_000u128)
+        );
+
+        let create = RetryableTicketEstimate {
+            to: Address::ZERO,
+            ..args
+        };
+        let tx = create
+            .submit_retryable_tx(U256::from(1), &pricing, U256::ZERO, 1)
+            .unwrap();
+        assert_eq!(tx.retry_to, None);
+    }
+}
@@ -0,0 +1,412 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use arb_alloy_predeploys::{topic, EVT_L2_TO_L1_TX};
+use thiserror::Error;
+
+/// Longest proof the L1 outbox accepts.
+pub const MAX_OUTBOX_PROOF_LEN: usize = 255;
+
+fn hash_pair(left: B256, right: B256) -> B256 {
+    let mut packed = [0u8; 64];
+    packed[..32].copy_from_slice(left.as_slice());
+    packed[32..].copy_from_slice(right.as_slice());
+    keccak256(packed)
+}
+
+/// Number of partials an accumulator of `size` leaves keeps.
+pub fn calc_num_partials(size: u64) -> u64 {
+    u64::from(u64::BITS - size.leading_zeros())
+}
+
+/// The hash ArbSys computes for `sendTxToL1`, which the outbox calls the
+/// item hash.
+pub fn send_hash(
+    caller: Address,
+    destination: Address,
+    arb_block_num: U256,
+    eth_block_num: U256,
+    timestamp: U256,
+    value: U256,
+    data: &[u8],
+) -> B256 {
+    let mut packed = Vec::with_capacity(20 + 20 + 4 * 32 + data.len());
+    packed.extend_from_slice(caller.as_slice());
+    packed.extend_from_slice(destination.as_slice());
+    for word in [arb_block_num, eth_block_num, timestamp, value] {
+        packed.extend_from_slice(&word.to_be_bytes::<32>());
+    }
+    packed.extend_from_slice(data);
+    keccak256(packed)
+}
+
+/// A leaf of the send tree: the keccak of the send hash.
+pub fn send_leaf(send_hash: B256) -> B256 {
+    keccak256(send_hash)
+}
+
+/// An interior node the accumulator completes while appending, emitted by
+/// ArbSys as `SendMerkleUpdate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MerkleTreeNodeEvent {
+    pub level: u64,
+    pub num_leaves: u64,
+    pub hash: B256,
+}
+
+impl MerkleTreeNodeEvent {
+    /// The `position` argument of `SendMerkleUpdate`: the level in the top
+    /// 64 bits above the leaf index.
+    pub fn position(&self) -> U256 {
+        (U256::from(self.level) << 192) | U256::from(self.num_leaves)
+    }
+}
+
+/// ArbOS's send merkle accumulator. Partial `i` holds the root of a full
+/// subtree of `2^i` leaves still waiting for its sibling, or zero.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SendMerkleAccumulator {
+    pub size: u64,
+    pub partials: Vec<B256>,
+}
+
+impl SendMerkleAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn append(&mut self, send_hash: B256) -> Vec<MerkleTreeNodeEvent> {
+        let prev_size = self.size;
+        self.size += 1;
+        let mut events = Vec::new();
+        let mut so_far = send_leaf(send_hash);
+        let mut level = 0u64;
+        loop {
+            if level == calc_num_partials(prev_size) {
+                self.set_partial(level, so_far);
+                return events;
+            }
+            let this_level = self.partial(level);
+            if this_level == B256::ZERO {
+                self.set_partial(level, so_far);
+                return events;
+            }
+            so_far = hash_pair(this_level, so_far);
+            self.set_partial(level, B256::ZERO);
+            level += 1;
+            events.push(MerkleTreeNodeEvent {
+                level,
+                num_leaves: prev_size,
+                hash: so_far,
+            });
+        }
+    }
+
+    pub fn root(&self) -> B256 {
+        let mut hash_so_far: Option<B256> = None;
+        let mut capacity_in_hash = 0u64;
+        let mut capacity = 1u64;
+        for level in 0..calc_num_partials(self.size) {
+            let partial = self.partial(level);
+            if partial != B256::ZERO {
+                hash_so_far = Some(match hash_so_far {
+                    None => {
+                        capacity_in_hash = capacity;
+                        partial
+                    }
+                    Some(mut h) => {
+                        while capacity_in_hash < capacity {
+                            h = hash_pair(h, B256::ZERO);
+                            capacity_in_hash *= 2;
+                        }
+                        capacity_in_hash = 2 * capacity;
+                        hash_pair(partial, h)
+                    }
+                });
+            }
+            capacity = capacity.saturating_mul(2);
+        }
+        hash_so_far.unwrap_or_default()
+    }
+
+    fn partial(&self, level: u64) -> B256 {
+        self.partials
+            .get(level as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn set_partial(&mut self, level: u64, hash: B256) {
+        let level = level as usize;
+        if self.partials.len() <= level {
+            self.partials.resize(level + 1, B256::ZERO);
+        }
+        self.partials[level] = hash;
+    }
+}
+
+/// What `NodeInterface.constructOutboxProof` returns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutboxProof {
+    pub send: B256,
+    pub root: B256,
+    pub proof: Vec<B256>,
+}
+
+/// Builds the proof for the send at `leaf` in a tree of all `sends` so far.
+/// Subtrees with no leaves count as zero hashes, like the accumulator's root.
+pub fn construct_outbox_proof(sends: &[B256], leaf: u64) -> Result<OutboxProof, OutboxError> {
+    let size = sends.len() as u64;
+    if leaf >= size {
+        return Err(OutboxError::LeafOutOfRange { leaf, size });
+    }
+    let mut layer: Vec<B256> = sends.iter().copied().map(send_leaf).collect();
+    let mut index = leaf as usize;
+    let mut proof = Vec::new();
+    while layer.len() > 1 {
+        proof.push(layer.get(index ^ 1).copied().unwrap_or_default());
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash_pair(pair[0], pair.get(1).copied().unwrap_or_default()))
+            .collect();
+        index /= 2;
+    }
+    Ok(OutboxProof {
+        send: sends[leaf as usize],
+        root: layer[0],
+        proof,
+    })
+}
+
+/// `Outbox.calculateMerkleRoot`.
+pub fn calculate_merkle_root(proof: &[B256], path: U256, send_hash: B256) -> B256 {
+    let mut h = send_leaf(send_hash);
+    for (i, node) in proof.iter().enumerate() {
+        h = if path.bit(i) {
+            hash_pair(*node, h)
+        } else {
+            hash_pair(h, *node)
+        };
+    }
+    h
+}
+
+/// The checks `Outbox.executeTransaction` applies to a proof before
+/// releasing a withdrawal.
+pub fn verify_outbox_proof(
+    root: B256,
+    send_hash: B256,
+    index: U256,
+    proof: &[B256],
+) -> Result<(), OutboxError> {
+    if proof.len() > MAX_OUTBOX_PROOF_LEN {
+        return Err(OutboxError::ProofTooLong(proof.len()));
+    }
+    if index >= U256::from(1) << proof.len() {
+        return Err(OutboxError::PathNotMinimal);
+    }
+    if calculate_merkle_root(proof, index, send_hash) != root {
+        return Err(OutboxError::InvalidRoot);
+    }
+    Ok(())
+}
+
+/// A decoded ArbSys `L2ToL1Tx` log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct L2ToL1Tx {
+    pub caller: Address,
+    pub destination: Address,
+    pub hash: U256,
+    pub position: U256,
+    pub arb_block_num: U256,
+    pub eth_block_num: U256,
+    pub timestamp: U256,
+    pub callvalue: U256,
+    pub data: Bytes,
+}
+
+impl L2ToL1Tx {
+    pub fn decode_log(topics: &[B256], data: &[u8]) -> Result<Self, OutboxError> {
+        let [sig, destination, hash, position] = topics else {
+            return Err(OutboxError::InvalidLog);
+        };
+        if sig.0 != topic(EVT_L2_TO_L1_TX) {
+            return Err(OutboxError::InvalidLog);
+        }
+        let word = |i: usize| -> Result<U256, OutboxError> {
+            data.get(i * 32..i * 32 + 32)
+                .map(U256::from_be_slice)
+                .ok_or(OutboxError::InvalidLog)
+        };
+        let offset = usize::try_from(word(5)?).map_err(|_| OutboxError::InvalidLog)?;
+        let len_end = offset.checked_add(32).ok_or(OutboxError::InvalidLog)?;
+        let len = data
+            .get(offset..len_end)
+            .map(U256::from_be_slice)
+            .and_then(|len| usize::try_from(len).ok())
+            .ok_or(OutboxError::InvalidLog)?;
+        let payload = len_end
+            .checked_add(len)
+            .and_then(|end| data.get(len_end..end))
+            .ok_or(OutboxError::InvalidLog)?;
+        Ok(Self {
+            caller: Address::from_word(B256::from(word(0)?)),
+            destination: Address::from_word(*destination),
+            hash: (*hash).into(),
+            position: (*position).into(),
+            arb_block_num: word(1)?,
+            eth_block_num: word(2)?,
+            timestamp: word(3)?,
+            callvalue: word(4)?,
+            data: Bytes::copy_from_slice(payload),
+        })
+    }
+
+    pub fn send_hash(&self) -> B256 {
+        send_hash(
+            self.caller,
+            self.destination,
+            self.arb_block_num,
+            self.eth_block_num,
+            self.timestamp,
+            self.callvalue,
+            &self.data,
+        )
+    }
+
+    /// The leaf index; ArbSys emits it as the position of a level 0 node.
+    pub fn leaf_index(&self) -> U256 {
+        self.position
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum OutboxError {
+    #[error("leaf {leaf} out of range for {size} sends")]
+    LeafOutOfRange { leaf: u64, size: u64 },
+    #[error("proof of length {0} is too long")]
+    ProofTooLong(usize),
+    #[error("path is not minimal for the proof length")]
+    PathNotMinimal,
+    #[error("proof does not lead to the send root")]
+    InvalidRoot,
+    #[error("not a valid L2ToL1Tx log")]
+    InvalidLog,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sends(n: u64) -> Vec<B256> {
+        (0..n)
+            .map(|i| keccak256(i.to_be_bytes()))
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn accumulator_root_matches_tree_for_all_small_sizes() {
+        let all = sends(33);
+        let mut acc = SendMerkleAccumulator::new();
+        assert_eq!(acc.root(), B256::ZERO);
+        for (i, send) in all.iter().enumerate() {
+            acc.append(*send);
+            let size = i + 1;
+            assert_eq!(acc.partials.len() as u64, calc_num_partials(size as u64));
+            let proof = construct_outbox_proof(&all[..size], 0).unwrap();
+            assert_eq!(acc.root(), proof.root, "size {size}");
+        }
+    }
+
+    #[test]
+    fn root_for_three_sends_pads_with_zero() {
+        let s = sends(3);
+        let mut acc = SendMerkleAccumulator::new();
+        for send in &s {
+            acc.append(*send);
+        }
+        let l: Vec<_> = s.iter().copied().map(send_leaf).collect();
+        let expected = hash_pair(hash_pair(l[0], l[1]), hash_pair(l[2], B256::ZERO));
+        assert_eq!(acc.root(), expected);
+    }
+
+    #[test]
+    fn append_emits_completed_nodes() {
+        let s = sends(4);
+        let mut acc = SendMerkleAccumulator::new();
+        assert!(acc.append(s[0]).is_empty());
+        let events = acc.append(s[1]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].level, 1);
+        assert_eq!(events[0].num_leaves, 1);
+        assert!(acc.append(s[2]).is_empty());
+        let events = acc.append(s[3]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].hash, acc.root());
+        assert_eq!(events[1].position(), (U256::from(2) << 192) | U256::from(3));
+    }
+
+    #[test]
+    fn proofs_verify_for_every_leaf() {
+        let s = sends(11);
+        for leaf in 0..11u64 {
+            let p = construct_outbox_proof(&s, leaf).unwrap();
+            assert_eq!(p.proof.len(), 4);
+            verify_outbox_proof(p.root, p.send, U256::from(leaf), &p.proof).unwrap();
+            assert_eq!(
+                verify_outbox_proof(p.root, p.send, U256::from(leaf ^ 1), &p.proof),
+                Err(OutboxError::InvalidRoot)
+            );
+        }
+        let p = construct_outbox_proof(&s, 3).unwrap();
+        assert_eq!(
+            verify_outbox_proof(p.root, p.send, U256::from(3 + 16), &p.proof),
+            Err(OutboxError::PathNotMinimal)
+        );
+        assert_eq!(
+            construct_outbox_proof(&s, 11),
+            Err(OutboxError::LeafOutOfRange { leaf: 11, size: 11 })
+        );
+    }
+
+    #[test]
+    fn decodes_l2_to_l1_tx_log() {
+        let caller = Address::with_last_byte(1);
+        let destination = Address::with_last_byte(2);
+        let payload = [0xde, 0xad];
+        let hash = send_hash(
+            caller,
+            destination,
+            U256::from(10),
+            U256::from(20),
+            U256::from(30),
+            U256::from(40),
+            &payload,
+        );
+        let topics = [
+            B256::from(topic(EVT_L2_TO_L1_TX)),
+            destination.into_word(),
+            hash,
+            B256::from(U256::from(5)),
+        ];
+        let mut data = Vec::new();
+        data.extend_from_slice(caller.into_word().as_slice());
+        for v in [10u64, 20, 30, 40, 6 * 32, 2] {
+            data.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        }
+        data.extend_from_slice(&payload);
+        data.resize(data.len() + 30, 0);
+
+        let log = L2ToL1Tx::decode_log(&topics, &data).unwrap();
+        assert_eq!(log.caller, caller);
+        assert_eq!(log.destination, destination);
+        assert_eq!(log.leaf_index(), U256::from(5));
+        assert_eq!(log.data, Bytes::copy_from_slice(&payload));
+        assert_eq!(log.send_hash(), hash);
+        assert_eq!(
+            L2ToL1Tx::decode_log(&topics[..3], &data),
+            Err(OutboxError::InvalidLog)
+        );
+    }
+}
@@ -3,9 +3,16 @@
 extern crate alloc;
 pub const RETRYABLE_LIFETIME_SECONDS: u64 = 7 * 24 * 60 * 60;
 pub const RETRYABLE_REAP_PRICE_UNITS: u64 = 58_000;
+pub const RETRYABLE_REAPS_PER_BLOCK: usize = 2;
 
+use alloc::collections::{BTreeMap, VecDeque};
 use alloc::vec::Vec;
-use alloy_primitives::{keccak256, B256};
+use alloy_primitives::{keccak256, Address, B256, U256};
+use arb_alloy_consensus::tx::{ArbRetryTx, ArbSubmitRetryableTx, ArbTxEnvelope};
+use arb_alloy_consensus::ArbOsVersion;
+use thiserror::Error;
+
+use crate::gas::TX_GAS;
 
 pub fn retryable_submission_fee(calldata_len: usize, l1_base_fee_wei: u128) -> u128 {
     let overhead: u128 = 1400;
@@ -14,6 +21,50 @@ pub fn retryable_submission_fee(calldata_len: usize, l1_base_fee_wei: u128) -> u
     (overhead + per_byte * bytes) * l1_base_fee_wei
 }
 
+pub fn checked_retryable_submission_fee(
+    calldata_len: usize,
+    l1_base_fee_wei: u128,
+) -> Result<u128, RetryableError> {
+    (calldata_len as u128)
+        .checked_mul(6)
+        .and_then(|v| v.checked_add(1400))
+        .and_then(|units| units.checked_mul(l1_base_fee_wei))
+        .ok_or(RetryableError::SubmissionFeeOverflow)
+}
+
+pub fn checked_retryable_submission_fee_u256(
+    calldata_len: usize,
+    l1_base_fee: U256,
+) -> Result<U256, RetryableError> {
+    let units = U256::from(1400u64) + U256::from(6u64) * U256::from(calldata_len);
+    units
+        .checked_mul(l1_base_fee)
+        .ok_or(RetryableError::SubmissionFeeOverflow)
+}
+
+/// Performs ArbOS's submission fee checks for `tx` and returns the fee charged.
+/// `from_balance` is the sender's balance before the deposit is minted.
+pub fn validate_max_submission_fee(
+    tx: &ArbSubmitRetryableTx,
+    from_balance: U256,
+) -> Result<U256, RetryableError> {
+    let have = from_balance.saturating_add(tx.deposit_value);
+    if have < tx.max_submission_fee {
+        return Err(RetryableError::InsufficientFundsForMaxSubmissionFee {
+            have,
+            want: tx.max_submission_fee,
+        });
+    }
+    let actual = checked_retryable_submission_fee_u256(tx.retry_data.len(), tx.l1_base_fee)?;
+    if actual > tx.max_submission_fee {
+        return Err(RetryableError::MaxSubmissionFeeTooLow {
+            max: tx.max_submission_fee,
+            actual,
+        });
+    }
+    Ok(actual)
+}
+
 pub fn escrow_address_from_ticket(ticket_id: [u8; 32]) -> [u8; 20] {
     let mut input = Vec::with_capacity(16 + 32);
     input.extend_from_slice(b"retryable escrow");
@@ -28,10 +79,428 @@ pub fn retryable_timeout_from(now_secs: u64) -> u64 {
     now_secs.saturating_add(RETRYABLE_LIFETIME_SECONDS)
 }
 
+pub fn submit_retryable_ticket_id(tx: &ArbSubmitRetryableTx) -> B256 {
+    keccak256(ArbTxEnvelope::SubmitRetryable(tx.clone()).encode_typed())
+}
+
+/// Nitro's `takeFunds`: removes up to `take` from `pool` and returns what was removed.
+fn take_funds(pool: &mut U256, take: U256) -> U256 {
+    if *pool < take {
+        core::mem::take(pool)
+    } else {
+        *pool -= take;
+        take
+    }
+}
+
+/// Debits `from` for a transfer to `to`, returning the amount moved. Like
+/// `util.TransferBalance`, a transfer the balance can't cover moves nothing.
+fn transfer_out(balance: &mut U256, from: Address, to: Address, amount: U256) -> U256 {
+    if *balance < amount {
+        return U256::ZERO;
+    }
+    if from != to {
+        *balance -= amount;
+    }
+    amount
+}
+
+/// Chain state the submit-retryable hook reads besides the transaction itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryableFeeContext {
+    pub l2_base_fee: U256,
+    pub min_base_fee: U256,
+    pub arbos_version: ArbOsVersion,
+    pub infra_fee_account_set: bool,
+    /// Balance of `tx.from` before the deposit is minted.
+    pub from_balance: U256,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubmitRetryableStatus {
+    InsufficientFundsForMaxSubmissionFee,
+    MaxSubmissionFeeTooLow,
+    /// The call value couldn't be escrowed; the submission fee was handed back
+    /// and the withheld part of it forwarded to `fee_refund_addr`.
+    InsufficientFundsForCallValue,
+    /// The ticket exists but the gas parameters didn't allow an auto-redeem.
+    CreatedWithoutAutoRedeem,
+    AutoRedeemScheduled,
+}
+
+impl SubmitRetryableStatus {
+    pub fn ticket_created(self) -> bool {
+        matches!(
+            self,
+            Self::CreatedWithoutAutoRedeem | Self::AutoRedeemScheduled
+        )
+    }
+}
+
+/// Every balance movement made while executing an `ArbSubmitRetryableTx`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubmitRetryableBreakdown {
+    pub status: SubmitRetryableStatus,
+    pub ticket_id: B256,
+    pub escrow: Address,
+    /// Minted to `tx.from`.
+    pub deposit: U256,
+    /// Kept by the network fee account.
+    pub submission_fee: U256,
+    /// Sent to `fee_refund_addr` out of the submission fee budget.
+    pub submission_fee_refund: U256,
+    pub escrowed_value: U256,
+    /// Sent to `fee_refund_addr` when no auto-redeem is scheduled.
+    pub gas_cost_refund: U256,
+    pub infra_fee: U256,
+    pub network_gas_fee: U256,
+    pub gas_price_refund: U256,
+    pub retry_tx: Option<ArbRetryTx>,
+}
+
+pub fn submit_retryable_breakdown(
+    tx: &ArbSubmitRetryableTx,
+    ctx: &RetryableFeeContext,
+) -> SubmitRetryableBreakdown {
+    let ticket_id = submit_retryable_ticket_id(tx);
+    let mut out = SubmitRetryableBreakdown {
+        status: SubmitRetryableStatus::InsufficientFundsForMaxSubmissionFee,
+        ticket_id,
+        escrow: Address::from(escrow_address_from_ticket(ticket_id.0)),
+        deposit: tx.deposit_value,
+        submission_fee: U256::ZERO,
+        submission_fee_refund: U256::ZERO,
+        escrowed_value: U256::ZERO,
+        gas_cost_refund: U256::ZERO,
+        infra_fee: U256::ZERO,
+        network_gas_fee: U256::ZERO,
+        gas_price_refund: U256::ZERO,
+        retry_tx: None,
+    };
+
+    let mut available_refund = tx.deposit_value;
+    take_funds(&mut available_refund, tx.retry_value);
+    let mut balance = ctx.from_balance.saturating_add(tx.deposit_value);
+
+    let submission_fee = match validate_max_submission_fee(tx, ctx.from_balance) {
+        Ok(fee) => fee,
+        Err(RetryableError::InsufficientFundsForMaxSubmissionFee { .. }) => return out,
+        Err(_) => {
+            out.status = SubmitRetryableStatus::MaxSubmissionFeeTooLow;
+            return out;
+        }
+    };
+
+    balance -= submission_fee;
+    let withheld_submission_fee = take_funds(&mut available_refund, submission_fee);
+    let excess = take_funds(
+        &mut available_refund,
+        tx.max_submission_fee - submission_fee,
+    );
+    out.submission_fee_refund = transfer_out(&mut balance, tx.from, tx.fee_refund_addr, excess);
+
+    if balance < tx.retry_value {
+        balance += submission_fee;
+        out.submission_fee_refund += transfer_out(
+            &mut balance,
+            tx.from,
+            tx.fee_refund_addr,
+            withheld_submission_fee,
+        );
+        out.status = SubmitRetryableStatus::InsufficientFundsForCallValue;
+        return out;
+    }
+    balance -= tx.retry_value;
+    out.submission_fee = submission_fee;
+    out.escrowed_value = tx.retry_value;
+
+    let base_fee = ctx.l2_base_fee;
+    let max_gas_cost = tx.gas_fee_cap.saturating_mul(U256::from(tx.gas));
+    if balance < max_gas_cost || tx.gas < TX_GAS || tx.gas_fee_cap < base_fee {
+        let refund = take_funds(&mut available_refund, max_gas_cost);
+        out.gas_cost_refund = transfer_out(&mut balance, tx.from, tx.fee_refund_addr, refund);
+        out.status = SubmitRetryableStatus::CreatedWithoutAutoRedeem;
+        return out;
+    }
+
+    let gas_cost = base_fee.saturating_mul(U256::from(tx.gas));
+    let mut network_cost = gas_cost;
+    if ctx.arbos_version.has_infra_fees() && ctx.infra_fee_account_set {
+        let infra_cost = ctx
+            .min_base_fee
+            .min(base_fee)
+            .saturating_mul(U256::from(tx.gas));
+        out.infra_fee = take_funds(&mut network_cost, infra_cost);
+    }
+    out.network_gas_fee = network_cost;
+    balance -= gas_cost;
+
+    let withheld_gas_funds = take_funds(&mut available_refund, gas_cost);
+    let gas_price_refund = (tx.gas_fee_cap - base_fee).saturating_mul(U256::from(tx.gas));
+    let gas_price_refund = take_funds(&mut available_refund, gas_price_refund);
+    out.gas_price_refund =
+        transfer_out(&mut balance, tx.from, tx.fee_refund_addr, gas_price_refund);
+    available_refund = available_refund
+        .saturating_add(withheld_gas_funds)
+        .saturating_add(withheld_submission_fee);
+
+    out.retry_tx = Some(ArbRetryTx {
+        chain_id: tx.chain_id,
+        nonce: 0,
+        from: tx.from,
+        gas_fee_cap: base_fee,
+        gas: tx.gas,
+        to: tx.retry_to,
+        value: tx.retry_value,
+        data: tx.retry_data.clone(),
+        ticket_id,
+        refund_to: tx.fee_refund_addr,
+        max_refund: available_refund,
+        submission_fee_refund: submission_fee,
+    });
+    out.status = SubmitRetryableStatus::AutoRedeemScheduled;
+    out
+}
+
+/// Execution outcome of an `ArbRetryTx` needed to settle its refunds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryRefundContext {
+    pub gas_used: u64,
+    pub success: bool,
+    pub min_base_fee: U256,
+    pub arbos_version: ArbOsVersion,
+    pub infra_fee_account_set: bool,
+}
+
+/// A refund paid from one fee account, split between `refund_to` (bounded by
+/// `max_refund`) and the retry's `from`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RefundSplit {
+    pub to_refund_address: U256,
+    pub to_sender: U256,
+}
+
+impl RefundSplit {
+    pub fn total(&self) -> U256 {
+        self.to_refund_address.saturating_add(self.to_sender)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryRefunds {
+    /// Geth's unused-gas refund to `from`, burned again by ArbOS.
+    pub undone_gas_refund: U256,
+    pub submission_fee: RefundSplit,
+    pub infra_fee: RefundSplit,
+    pub network_fee: RefundSplit,
+}
+
+fn split_refund(max_refund: &mut U256, amount: U256) -> RefundSplit {
+    let to_refund_address = take_funds(max_refund, amount);
+    RefundSplit {
+        to_refund_address,
+        to_sender: amount - to_refund_address,
+    }
+}
+
+pub fn retry_tx_refunds(tx: &ArbRetryTx, ctx: &RetryRefundContext) -> RetryRefunds {
+    let base_fee = tx.gas_fee_cap;
+    let gas_left = tx.gas.saturating_sub(ctx.gas_used);
+    let gas_refund = base_fee.saturating_mul(U256::from(gas_left));
+    let mut max_refund = tx.max_refund;
+
+    let submission_fee = if ctx.success {
+        split_refund(&mut max_refund, tx.submission_fee_refund)
+    } else {
+        take_funds(&mut max_refund, tx.submission_fee_refund);
+        RefundSplit::default()
+    };
+    take_funds(
+        &mut max_refund,
+        base_fee.saturating_mul(U256::from(ctx.gas_used)),
+    );
+
+    let mut network_refund = gas_refund;
+    let mut infra_fee = RefundSplit::default();
+    if ctx.arbos_version.has_infra_fees() && ctx.infra_fee_account_set {
+        let infra_refund = ctx
+            .min_base_fee
+            .min(base_fee)
+            .saturating_mul(U256::from(gas_left));
+        let infra_refund = take_funds(&mut network_refund, infra_refund);
+        infra_fee = split_refund(&mut max_refund, infra_refund);
+    }
+    let network_fee = split_refund(&mut max_refund, network_refund);
+
+    RetryRefunds {
+        undone_gas_refund: gas_refund,
+        submission_fee,
+        infra_fee,
+        network_fee,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TicketTimeout {
+    timeout: u64,
+    windows_left: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReapOutcome {
+    QueueEmpty,
+    /// The head ticket was already deleted, its stale entry was dropped.
+    DiscardedStale(B256),
+    NotExpired(B256),
+    /// The head ticket had a keepalive window left; its timeout moved one lifetime ahead.
+    WindowConsumed(B256),
+    Reaped(B256),
+}
+
+/// ArbOS's retryable timeout queue together with the per-ticket timeout and
+/// remaining keepalive windows it consults.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetryableTimeoutQueue {
+    queue: VecDeque<B256>,
+    tickets: BTreeMap<B256, TicketTimeout>,
+}
+
+impl RetryableTimeoutQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<B256> {
+        self.queue.front().copied()
+    }
+
+    /// Registers a new ticket and returns its timeout.
+    pub fn create(&mut self, ticket_id: B256, now_secs: u64) -> u64 {
+        let timeout = retryable_timeout_from(now_secs);
+        self.tickets.insert(
+            ticket_id,
+            TicketTimeout {
+                timeout,
+                windows_left: 0,
+            },
+        );
+        self.queue.push_back(ticket_id);
+        timeout
+    }
+
+    /// Mirrors `OpenRetryable`: a ticket whose stored timeout has passed is
+    /// treated as gone even if it still has windows left to consume.
+    pub fn is_live(&self, ticket_id: B256, now_secs: u64) -> bool {
+        self.tickets
+            .get(&ticket_id)
+            .is_some_and(|t| t.timeout >= now_secs)
+    }
+
+    /// The timeout including all keepalive windows still queued.
+    pub fn calculate_timeout(&self, ticket_id: B256) -> Option<u64> {
+        self.tickets.get(&ticket_id).map(|t| {
+            t.timeout
+                .saturating_add(t.windows_left.saturating_mul(RETRYABLE_LIFETIME_SECONDS))
+        })
+    }
+
+    /// Deletes a ticket after a successful redeem or a cancel. Its queue
+    /// entries stay behind and are discarded when they reach the head.
+    pub fn delete(&mut self, ticket_id: B256) -> bool {
+        self.tickets.remove(&ticket_id).is_some()
+    }
+
+    /// Extends a ticket by one lifetime, returning the new timeout. The caller
+    /// is charged `RETRYABLE_REAP_PRICE_UNITS` for the extra queue entry.
+    pub fn keepalive(&mut self, ticket_id: B256, now_secs: u64) -> Result<u64, RetryableError> {
+        if !self.is_live(ticket_id, now_secs) {
+            return Err(RetryableError::TicketNotFound(ticket_id));
+        }
+        let timeout = self.calculate_timeout(ticket_id).unwrap_or_default();
+        if timeout > now_secs.saturating_add(RETRYABLE_LIFETIME_SECONDS) {
+            return Err(RetryableError::TimeoutTooFarInFuture);
+        }
+        self.queue.push_back(ticket_id);
+        if let Some(t) = self.tickets.get_mut(&ticket_id) {
+            t.windows_left += 1;
+        }
+        Ok(timeout.saturating_add(RETRYABLE_LIFETIME_SECONDS))
+    }
+
+    /// One `TryToReapOneRetryable` step.
+    pub fn try_reap_one(&mut self, now_secs: u64) -> ReapOutcome {
+        let Some(id) = self.peek() else {
+            return ReapOutcome::QueueEmpty;
+        };
+        let Some(entry) = self.tickets.get_mut(&id) else {
+            self.queue.pop_front();
+            return ReapOutcome::DiscardedStale(id);
+        };
+        if entry.timeout >= now_secs {
+            return ReapOutcome::NotExpired(id);
+        }
+        self.queue.pop_front();
+        if entry.windows_left == 0 {
+            self.tickets.remove(&id);
+            return ReapOutcome::Reaped(id);
+        }
+        entry.timeout = entry.timeout.saturating_add(RETRYABLE_LIFETIME_SECONDS);
+        entry.windows_left -= 1;
+        ReapOutcome::WindowConsumed(id)
+    }
+
+    /// The reaping ArbOS performs at the start of each block, returning the
+    /// tickets deleted.
+    pub fn reap_block(&mut self, block_timestamp: u64) -> Vec<B256> {
+        (0..RETRYABLE_REAPS_PER_BLOCK)
+            .filter_map(|_| match self.try_reap_one(block_timestamp) {
+                ReapOutcome::Reaped(id) => Some(id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Replays `reap_block` over the given block timestamps and returns the
+    /// timestamp of the block that deletes `ticket_id`, if any does.
+    pub fn predict_deletion<I>(&self, ticket_id: B256, block_timestamps: I) -> Option<u64>
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        if !self.tickets.contains_key(&ticket_id) {
+            return None;
+        }
+        let mut sim = self.clone();
+        block_timestamps
+            .into_iter()
+            .find(|&ts| sim.reap_block(ts).contains(&ticket_id))
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RetryableError {
+    #[error("retryable submission fee overflows")]
+    SubmissionFeeOverflow,
+    #[error("insufficient funds for max submission fee: have {have} want {want}")]
+    InsufficientFundsForMaxSubmissionFee { have: U256, want: U256 },
+    #[error("max submission fee {max} is less than the actual submission fee {actual}")]
+    MaxSubmissionFeeTooLow { max: U256, actual: U256 },
+    #[error("retryable ticket {0} not found")]
+    TicketNotFound(B256),
+    #[error("timeout too far into the future")]
+    TimeoutTooFarInFuture,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_primitives::{keccak256, B256};
 
     #[test]
     fn retryable_constants_match_nitro() {
@@ -95,4 +564,270 @@ mod tests {
         let res = retryable_timeout_from(near_max);
         assert_eq!(res, u64::MAX);
     }
+    #[test]
+    fn checked_submission_fee_matches_unchecked_and_detects_overflow() {
+        assert_eq!(
+            checked_retryable_submission_fee(100, 1_000),
+            Ok(retryable_submission_fee(100, 1_000))
+        );
+        assert_eq!(
+            checked_retryable_submission_fee(0, u128::MAX),
+            Err(RetryableError::SubmissionFeeOverflow)
+        );
+        assert_eq!(
+            checked_retryable_submission_fee_u256(100, U256::from(1_000u64)),
+            Ok(U256::from(retryable_submission_fee(100, 1_000)))
+        );
+        assert_eq!(
+            checked_retryable_submission_fee_u256(0, U256::MAX),
+            Err(RetryableError::SubmissionFeeOverflow)
+        );
+        assert_eq!(
+            checked_retryable_submission_fee_u256(0, U256::from(u128::MAX)),
+            Ok(U256::from(u128::MAX) * U256::from(1400u64))
+        );
+    }
+
+    #[test]
+    fn validate_max_submission_fee_mirrors_arbos_checks() {
+        let mut tx = sample_submit();
+        assert_eq!(
+            validate_max_submission_fee(&tx, U256::ZERO),
+            Ok(U256::from(14_600u64))
+        );
+
+        tx.max_submission_fee = U256::from(14_599u64);
+        assert_eq!(
+            validate_max_submission_fee(&tx, U256::ZERO),
+            Err(RetryableError::MaxSubmissionFeeTooLow {
+                max: U256::from(14_599u64),
+                actual: U256::from(14_600u64),
+            })
+        );
+
+        tx.max_submission_fee = U256::from(20_000u64);
+        tx.deposit_value = U256::from(5_000u64);
+        assert!(matches!(
+            validate_max_submission_fee(&tx, U256::ZERO),
+            Err(RetryableError::InsufficientFundsForMaxSubmissionFee { .. })
+        ));
+        assert!(validate_max_submission_fee(&tx, U256::from(15_000u64)).is_ok());
+
+        tx.l1_base_fee = U256::MAX;
+        tx.max_submission_fee = U256::MAX;
+        tx.deposit_value = U256::MAX;
+        assert_eq!(
+            validate_max_submission_fee(&tx, U256::ZERO),
+            Err(RetryableError::SubmissionFeeOverflow)
+        );
+    }
+
+    #[test]
+    fn timeout_queue_reaps_expired_head_only() {
+        let a = B256::repeat_byte(0xaa);
+        let b = B256::repeat_byte(0xbb);
+        let mut q = RetryableTimeoutQueue::new();
+        assert_eq!(q.try_reap_one(0), ReapOutcome::QueueEmpty);
+        let ta = q.create(a, 100);
+        q.create(b, 200);
+        assert_eq!(q.peek(), Some(a));
+        assert_eq!(q.try_reap_one(ta), ReapOutcome::NotExpired(a));
+        assert_eq!(q.try_reap_one(ta + 1), ReapOutcome::Reaped(a));
+        assert!(!q.is_live(a, ta));
+        assert_eq!(q.peek(), Some(b));
+    }
+
+    #[test]
+    fn timeout_queue_discards_deleted_tickets() {
+        let a = B256::repeat_byte(0xaa);
+        let mut q = RetryableTimeoutQueue::new();
+        q.create(a, 100);
+        assert!(q.delete(a));
+        assert_eq!(q.try_reap_one(0), ReapOutcome::DiscardedStale(a));
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn keepalive_adds_window_and_reinserts() {
+        let a = B256::repeat_byte(0xaa);
+        let mut q = RetryableTimeoutQueue::new();
+        let timeout = q.create(a, 100);
+        let extended = q.keepalive(a, 100).unwrap();
+        assert_eq!(extended, timeout + RETRYABLE_LIFETIME_SECONDS);
+        assert_eq!(q.calculate_timeout(a), Some(extended));
+        assert_eq!(q.len(), 2);
+        assert_eq!(
+            q.keepalive(a, 100),
+            Err(RetryableError::TimeoutTooFarInFuture)
+        );
+        assert_eq!(
+            q.keepalive(B256::ZERO, 100),
+            Err(RetryableError::TicketNotFound(B256::ZERO))
+        );
+
+        assert_eq!(q.try_reap_one(timeout + 1), ReapOutcome::WindowConsumed(a));
+        assert!(q.is_live(a, timeout + 1));
+        assert_eq!(q.try_reap_one(extended + 1), ReapOutcome::Reaped(a));
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn reap_block_is_bounded_and_predicts_deletion() {
+        let ids: Vec<B256> = (1u8..=5).map(B256::repeat_byte).collect();
+        let mut q = RetryableTimeoutQueue::new();
+        for id in &ids {
+            q.create(*id, 0);
+        }
+        let after = RETRYABLE_LIFETIME_SECONDS + 1;
+        assert_eq!(q.predict_deletion(ids[4], [0, after, after + 1]), None);
+        assert_eq!(
+            q.predict_deletion(ids[4], [after, after + 1, after + 2, after + 3]),
+            Some(after + 2)
+        );
+        assert_eq!(q.reap_block(after), ids[..2].to_vec());
+        assert_eq!(q.len(), 3);
+        assert_eq!(q.predict_deletion(B256::ZERO, [after]), None);
+    }
+
+    fn sample_submit() -> ArbSubmitRetryableTx {
+        ArbSubmitRetryableTx {
+            chain_id: U256::from(42161u64),
+            request_id: B256::repeat_byte(0x11),
+            from: Address::repeat_byte(0x01),
+            l1_base_fee: U256::from(10u64),
+            deposit_value: U256::from(10_000_000u64),
+            gas_fee_cap: U256::from(20u64),
+            gas: 100_000,
+            retry_to: Some(Address::repeat_byte(0x02)),
+            retry_value: U256::from(1_000u64),
+            beneficiary: Address::repeat_byte(0x03),
+            max_submission_fee: U256::from(50_000u64),
+            fee_refund_addr: Address::repeat_byte(0x04),
+            retry_data: alloc::vec![0u8; 10].into(),
+        }
+    }
+
+    fn sample_ctx() -> RetryableFeeContext {
+        RetryableFeeContext {
+            l2_base_fee: U256::from(10u64),
+            min_base_fee: U256::from(4u64),
+            arbos_version: ArbOsVersion::V20,
+            infra_fee_account_set: true,
+            from_balance: U256::ZERO,
+        }
+    }
+
+    #[test]
+    fn submit_retryable_auto_redeem_accounts_for_whole_deposit() {
+        let tx = sample_submit();
+        let b = submit_retryable_breakdown(&tx, &sample_ctx());
+        assert_eq!(b.status, SubmitRetryableStatus::AutoRedeemScheduled);
+        assert_eq!(b.submission_fee, U256::from((1400u64 + 6 * 10) * 10));
+        assert_eq!(b.submission_fee_refund, U256::from(50_000u64 - 14_600));
+        assert_eq!(b.escrowed_value, tx.retry_value);
+        assert_eq!(b.infra_fee, U256::from(4u64 * 100_000));
+        assert_eq!(b.network_gas_fee, U256::from(6u64 * 100_000));
+        assert_eq!(b.gas_price_refund, U256::from(10u64 * 100_000));
+
+        let retry = b.retry_tx.expect("auto-redeem");
+        assert_eq!(retry.ticket_id, b.ticket_id);
+        assert_eq!(retry.gas_fee_cap, U256::from(10u64));
+        assert_eq!(retry.submission_fee_refund, b.submission_fee);
+        let spent = b.submission_fee_refund + b.escrowed_value + b.gas_price_refund;
+        assert_eq!(retry.max_refund, tx.deposit_value - spent);
+        assert_eq!(
+            b.escrow,
+            Address::from(escrow_address_from_ticket(b.ticket_id.0))
+        );
+    }
+
+    #[test]
+    fn submit_retryable_rejects_low_max_submission_fee() {
+        let mut tx = sample_submit();
+        tx.max_submission_fee = U256::from(14_599u64);
+        let b = submit_retryable_breakdown(&tx, &sample_ctx());
+        assert_eq!(b.status, SubmitRetryableStatus::MaxSubmissionFeeTooLow);
+        assert!(!b.status.ticket_created());
+        assert_eq!(b.submission_fee, U256::ZERO);
+
+        tx.deposit_value = U256::from(1u64);
+        let b = submit_retryable_breakdown(&tx, &sample_ctx());
+        assert_eq!(
+            b.status,
+            SubmitRetryableStatus::InsufficientFundsForMaxSubmissionFee
+        );
+    }
+
+    #[test]
+    fn submit_retryable_unescrowable_call_value_refunds_submission_fee() {
+        let mut tx = sample_submit();
+        tx.deposit_value = U256::from(50_000u64);
+        tx.retry_value = U256::from(60_000u64);
+        let b = submit_retryable_breakdown(&tx, &sample_ctx());
+        assert_eq!(
+            b.status,
+            SubmitRetryableStatus::InsufficientFundsForCallValue
+        );
+        assert_eq!(b.submission_fee, U256::ZERO);
+        assert_eq!(b.escrowed_value, U256::ZERO);
+        assert_eq!(b.submission_fee_refund, U256::ZERO);
+    }
+
+    #[test]
+    fn submit_retryable_without_auto_redeem_refunds_gas_budget() {
+        let mut tx = sample_submit();
+        tx.gas_fee_cap = U256::from(5u64);
+        let b = submit_retryable_breakdown(&tx, &sample_ctx());
+        assert_eq!(b.status, SubmitRetryableStatus::CreatedWithoutAutoRedeem);
+        assert!(b.retry_tx.is_none());
+        assert_eq!(b.gas_cost_refund, U256::from(5u64 * 100_000));
+
+        let mut tx = sample_submit();
+        tx.gas = 20_000;
+        let b = submit_retryable_breakdown(&tx, &sample_ctx());
+        assert_eq!(b.status, SubmitRetryableStatus::CreatedWithoutAutoRedeem);
+    }
+
+    #[test]
+    fn submit_retryable_infra_fee_only_from_arbos_11() {
+        let tx = sample_submit();
+        let mut ctx = sample_ctx();
+        ctx.arbos_version = ArbOsVersion::V10;
+        let b = submit_retryable_breakdown(&tx, &ctx);
+        assert_eq!(b.infra_fee, U256::ZERO);
+        assert_eq!(b.network_gas_fee, U256::from(10u64 * 100_000));
+    }
+
+    #[test]
+    fn retry_refunds_split_by_max_refund() {
+        let b = submit_retryable_breakdown(&sample_submit(), &sample_ctx());
+        let retry = b.retry_tx.unwrap();
+        let ctx = RetryRefundContext {
+            gas_used: 40_000,
+            success: true,
+            min_base_fee: U256::from(4u64),
+            arbos_version: ArbOsVersion::V20,
+            infra_fee_account_set: true,
+        };
+        let r = retry_tx_refunds(&retry, &ctx);
+        assert_eq!(r.undone_gas_refund, U256::from(10u64 * 60_000));
+        assert_eq!(r.submission_fee.total(), retry.submission_fee_refund);
+        assert_eq!(r.infra_fee.total(), U256::from(4u64 * 60_000));
+        assert_eq!(r.network_fee.total(), U256::from(6u64 * 60_000));
+        assert_eq!(r.infra_fee.to_sender, U256::ZERO);
+
+        let mut small = retry.clone();
+        small.max_refund = U256::from(100u64);
+        let r = retry_tx_refunds(&small, &ctx);
+        assert_eq!(r.submission_fee.to_refund_address, U256::from(100u64));
+        assert_eq!(r.network_fee.to_refund_address, U256::ZERO);
+        assert_eq!(r.network_fee.to_sender, U256::from(6u64 * 60_000));
+
+        let failed = RetryRefundContext {
+            success: false,
+            ..ctx
+        };
+        let r = retry_tx_refunds(&retry, &failed);
+        assert_eq!(r.submission_fee, RefundSplit::default());
+    }
 }
@@ -0,0 +1,553 @@
+#![allow(dead_code)]
+
+use alloc::vec::Vec;
+use alloy_primitives::{Bytes, U256};
+#[cfg(feature = "brotli")]
+use arb_alloy_consensus::messages::MAX_L2_MESSAGE_SIZE;
+use arb_alloy_consensus::messages::{L1IncomingMessage, L1IncomingMessageHeader, L1MessageKind};
+pub use arb_alloy_consensus::sequencer_batch::{
+    BatchHeader, BLOB_HASHES_HEADER_FLAG, BROTLI_MESSAGE_HEADER_BYTE, DAS_MESSAGE_HEADER_FLAG,
+    L1_AUTHENTICATED_MESSAGE_HEADER_FLAG, SEQUENCER_BATCH_HEADER_LEN, TREE_DAS_MESSAGE_HEADER_FLAG,
+    ZEROHEAVY_MESSAGE_HEADER_FLAG,
+};
+use thiserror::Error;
+
+#[cfg(feature = "brotli")]
+use crate::compression::brotli_decompress;
+use crate::l1_pricing::BATCH_POSTER_ADDRESS;
+
+pub const MAX_DECOMPRESSED_LEN: usize = 16 * 1024 * 1024;
+pub const MAX_SEGMENTS_PER_SEQUENCER_MESSAGE: usize = 100 * 1024;
+
+const KNOWN_HEADER_BITS: u8 = DAS_MESSAGE_HEADER_FLAG
+    | TREE_DAS_MESSAGE_HEADER_FLAG
+    | L1_AUTHENTICATED_MESSAGE_HEADER_FLAG
+    | ZEROHEAVY_MESSAGE_HEADER_FLAG
+    | BLOB_HASHES_HEADER_FLAG
+    | BROTLI_MESSAGE_HEADER_BYTE;
+
+pub const BATCH_SEGMENT_KIND_L2_MESSAGE: u8 = 0;
+pub const BATCH_SEGMENT_KIND_L2_MESSAGE_BROTLI: u8 = 1;
+pub const BATCH_SEGMENT_KIND_DELAYED_MESSAGES: u8 = 2;
+pub const BATCH_SEGMENT_KIND_ADVANCE_TIMESTAMP: u8 = 3;
+pub const BATCH_SEGMENT_KIND_ADVANCE_L1_BLOCK_NUMBER: u8 = 4;
+
+fn has_bits(byte: u8, bits: u8) -> bool {
+    byte & bits == bits
+}
+
+// Unlike `Ord::clamp`, tolerates `max < min` the way Nitro does.
+fn clamp(value: u64, min: u64, max: u64) -> u64 {
+    if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
+
+/// Where a batch's payload lives, according to its header flag byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchPayloadKind {
+    Empty,
+    Brotli,
+    /// An AnyTrust DAS certificate.
+    Das,
+    /// Versioned hashes of EIP-4844 blobs holding the payload.
+    Blob,
+    /// A zeroheavy-encoded payload. Nitro decodes these before looking for
+    /// the brotli flag; this crate doesn't implement the decoder, which is a
+    /// known divergence from Nitro's `parseSequencerMessage`.
+    Zeroheavy,
+    Unknown(u8),
+}
+
+impl BatchPayloadKind {
+    pub fn classify(payload: &[u8]) -> Result<Self, BatchError> {
+        let Some(&flag) = payload.first() else {
+            return Ok(Self::Empty);
+        };
+        // The inbox contract validated authenticated header bytes, so one we
+        // don't know means this code is out of date rather than the batch bad.
+        if has_bits(flag, L1_AUTHENTICATED_MESSAGE_HEADER_FLAG) && flag & !KNOWN_HEADER_BITS != 0 {
+            return Err(BatchError::UnsupportedHeaderByte(flag));
+        }
+        Ok(if has_bits(flag, DAS_MESSAGE_HEADER_FLAG) {
+            Self::Das
+        } else if has_bits(flag, BLOB_HASHES_HEADER_FLAG) {
+            Self::Blob
+        } else if has_bits(flag, ZEROHEAVY_MESSAGE_HEADER_FLAG) {
+            Self::Zeroheavy
+        } else if flag == BROTLI_MESSAGE_HEADER_BYTE {
+            Self::Brotli
+        } else {
+            Self::Unknown(flag)
+        })
+    }
+}
+
+/// A message sequenced by a batch, in the order the inbox multiplexer
+/// yields them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchMessage {
+    L2 {
+        timestamp: u64,
+        l1_block_number: u64,
+        data: Bytes,
+        delayed_messages_read: u64,
+    },
+    /// A pointer to the delayed inbox message at `index`.
+    Delayed { index: u64 },
+    /// Nitro's `InvalidL1Message`, sequenced for a segment that reads past
+    /// the batch's delayed message count, fails to decompress or has an
+    /// unknown kind, and for a batch with nothing else to sequence.
+    Invalid { delayed_messages_read: u64 },
+}
+
+impl BatchMessage {
+    pub fn delayed_messages_read(&self) -> u64 {
+        match self {
+            BatchMessage::L2 {
+                delayed_messages_read,
+                ..
+            }
+            | BatchMessage::Invalid {
+                delayed_messages_read,
+            } => *delayed_messages_read,
+            BatchMessage::Delayed { index } => index + 1,
+        }
+    }
+
+    /// The `L1IncomingMessage` Nitro builds for a sequenced L2 message.
+    pub fn to_incoming_message(&self) -> Option<L1IncomingMessage> {
+        let BatchMessage::L2 {
+            timestamp,
+            l1_block_number,
+            data,
+            ..
+        } = self
+        else {
+            return None;
+        };
+        Some(L1IncomingMessage {
+            header: L1IncomingMessageHeader {
+                kind: L1MessageKind::L2Message.as_u8(),
+                poster: BATCH_POSTER_ADDRESS,
+                block_number: *l1_block_number,
+                timestamp: *timestamp,
+                request_id: None,
+                l1_base_fee: U256::ZERO,
+            },
+            l2_msg: data.clone(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequencerBatch {
+    pub header: BatchHeader,
+    pub payload_kind: BatchPayloadKind,
+    pub segments: Vec<Bytes>,
+    pub messages: Vec<BatchMessage>,
+}
+
+/// Decodes batch data as posted to the sequencer inbox. The payload must be
+/// inline; DAS and blob batches go through `decode_resolved_batch` once
+/// their payload is recovered. Zeroheavy payloads, which Nitro accepts, are
+/// rejected with `BatchError::UnsupportedZeroheavy`.
+#[cfg(feature = "brotli")]
+pub fn decode_sequencer_batch(
+    data: &[u8],
+    delayed_messages_read: u64,
+) -> Result<SequencerBatch, BatchError> {
+    let (header, payload) = BatchHeader::parse(data).map_err(|_| BatchError::MissingHeader)?;
+    match BatchPayloadKind::classify(payload)? {
+        kind @ (BatchPayloadKind::Das | BatchPayloadKind::Blob) => {
+            Err(BatchError::ExternalPayload(kind))
+        }
+        BatchPayloadKind::Zeroheavy => Err(BatchError::UnsupportedZeroheavy),
+        _ => Ok(decode_resolved_batch(
+            header,
+            payload,
+            delayed_messages_read,
+        )),
+    }
+}
+
+/// Decodes a batch whose payload, starting at its flag byte, is already
+/// available. Payloads that fail to decompress or aren't brotli yield no
+/// segments, leaving only the delayed messages the header accounts for.
+#[cfg(feature = "brotli")]
+pub fn decode_resolved_batch(
+    header: BatchHeader,
+    payload: &[u8],
+    delayed_messages_read: u64,
+) -> SequencerBatch {
+    let payload_kind = BatchPayloadKind::classify(payload).unwrap_or(BatchPayloadKind::Unknown(0));
+    let segments = match payload.split_first() {
+        Some((&BROTLI_MESSAGE_HEADER_BYTE, compressed)) => brotli_segments(compressed),
+        _ => Vec::new(),
+    };
+    let messages = sequence_messages(&header, &segments, delayed_messages_read);
+    SequencerBatch {
+        header,
+        payload_kind,
+        segments,
+        messages,
+    }
+}
+
+#[cfg(feature = "brotli")]
+fn brotli_segments(compressed: &[u8]) -> Vec<Bytes> {
+    let Some(decompressed) = brotli_decompress(compressed, MAX_DECOMPRESSED_LEN) else {
+        return Vec::new();
+    };
+    let mut buf = &decompressed[..];
+    let mut segments = Vec::new();
+    while !buf.is_empty() && segments.len() < MAX_SEGMENTS_PER_SEQUENCER_MESSAGE {
+        match alloy_rlp::Header::decode_bytes(&mut buf, false) {
+            Ok(segment) => segments.push(Bytes::copy_from_slice(segment)),
+            Err(_) => break,
+        }
+    }
+    segments
+}
+
+/// Walks the segments the way Nitro's inbox multiplexer pops them, tracking
+/// the timestamp and L1 block advances and reading delayed messages up to the
+/// header's count. Every batch sequences at least one message, and it ends
+/// once no L2 or delayed message segment is left and the header's delayed
+/// messages have all been read.
+#[cfg(feature = "brotli")]
+pub fn sequence_messages(
+    header: &BatchHeader,
+    segments: &[Bytes],
+    mut delayed_messages_read: u64,
+) -> Vec<BatchMessage> {
+    use alloy_rlp::Decodable;
+
+    let invalid = BatchMessage::Invalid {
+        delayed_messages_read: header.after_delayed_messages,
+    };
+    let mut messages = Vec::new();
+    let mut timestamp = header.min_timestamp;
+    let mut block_number = header.min_l1_block;
+    let mut i = 0;
+    loop {
+        while let Some(segment) = segments.get(i) {
+            let advance = match segment.first() {
+                None => {
+                    i += 1;
+                    continue;
+                }
+                Some(&kind)
+                    if kind == BATCH_SEGMENT_KIND_ADVANCE_TIMESTAMP
+                        || kind == BATCH_SEGMENT_KIND_ADVANCE_L1_BLOCK_NUMBER =>
+                {
+                    (kind, u64::decode(&mut &segment[1..]))
+                }
+                Some(_) => break,
+            };
+            match advance {
+                (BATCH_SEGMENT_KIND_ADVANCE_TIMESTAMP, Ok(by)) => {
+                    timestamp = timestamp.wrapping_add(by)
+                }
+                (_, Ok(by)) => block_number = block_number.wrapping_add(by),
+                (_, Err(_)) => {}
+            }
+            i += 1;
+        }
+        let clamped_timestamp = clamp(timestamp, header.min_timestamp, header.max_timestamp);
+        let clamped_block = clamp(block_number, header.min_l1_block, header.max_l1_block);
+
+        // Past the last segment, the delayed messages the header accounts
+        // for are read through virtual delayed message segments.
+        let (kind, body) = match segments.get(i) {
+            Some(segment) => {
+                i += 1;
+                (segment[0], &segment[1..])
+            }
+            None => (BATCH_SEGMENT_KIND_DELAYED_MESSAGES, &[][..]),
+        };
+        let message = match kind {
+            BATCH_SEGMENT_KIND_L2_MESSAGE => BatchMessage::L2 {
+                timestamp: clamped_timestamp,
+                l1_block_number: clamped_block,
+                data: Bytes::copy_from_slice(body),
+                delayed_messages_read,
+            },
+            BATCH_SEGMENT_KIND_L2_MESSAGE_BROTLI => {
+                match brotli_decompress(body, MAX_L2_MESSAGE_SIZE) {
+                    Some(data) => BatchMessage::L2 {
+                        timestamp: clamped_timestamp,
+                        l1_block_number: clamped_block,
+                        data: Bytes::from(data),
+                        delayed_messages_read,
+                    },
+                    None => invalid.clone(),
+                }
+            }
+            BATCH_SEGMENT_KIND_DELAYED_MESSAGES => {
+                if delayed_messages_read >= header.after_delayed_messages {
+                    invalid.clone()
+                } else {
+                    delayed_messages_read += 1;
+                    BatchMessage::Delayed {
+                        index: delayed_messages_read - 1,
+                    }
+                }
+            }
+            _ => invalid.clone(),
+        };
+        messages.push(message);
+
+        // Nitro's `IsCachedSegementLast`.
+        let more_segments = segments[i..].iter().any(|segment| {
+            matches!(
+                segment.first(),
+                Some(
+                    &(BATCH_SEGMENT_KIND_L2_MESSAGE
+                        | BATCH_SEGMENT_KIND_L2_MESSAGE_BROTLI
+                        | BATCH_SEGMENT_KIND_DELAYED_MESSAGES)
+                )
+            )
+        });
+        if !more_segments && delayed_messages_read >= header.after_delayed_messages {
+            break;
+        }
+    }
+    messages
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum BatchError {
+    #[error("sequencer message missing L1 header")]
+    MissingHeader,
+    #[error("batch has unsupported header byte {0:#04x}")]
+    UnsupportedHeaderByte(u8),
+    #[error("zeroheavy batch payloads are not supported")]
+    UnsupportedZeroheavy,
+    #[error("batch payload is not inline: {0:?}")]
+    ExternalPayload(BatchPayloadKind),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "brotli")]
+    use crate::compression::{brotli_compress, BROTLI_MAX_LEVEL};
+
+    fn header() -> BatchHeader {
+        BatchHeader {
+            min_timestamp: 1_000,
+            max_timestamp: 1_100,
+            min_l1_block: 50,
+            max_l1_block: 60,
+            after_delayed_messages: 3,
+        }
+    }
+
+    #[test]
+    fn header_roundtrip() {
+        let mut data = header().encode().to_vec();
+        data.push(0xaa);
+        let (parsed, payload) = BatchHeader::parse(&data).unwrap();
+        assert_eq!(parsed, header());
+        assert_eq!(payload, &[0xaa]);
+        #[cfg(feature = "brotli")]
+        assert_eq!(
+            decode_sequencer_batch(&data[..39], 0),
+            Err(BatchError::MissingHeader)
+        );
+    }
+
+    #[test]
+    fn header_flags_classify_payload() {
+        use BatchPayloadKind::*;
+        assert_eq!(BatchPayloadKind::classify(&[]), Ok(Empty));
+        assert_eq!(BatchPayloadKind::classify(&[0x00]), Ok(Brotli));
+        assert_eq!(BatchPayloadKind::classify(&[0x80]), Ok(Das));
+        assert_eq!(BatchPayloadKind::classify(&[0x88]), Ok(Das));
+        assert_eq!(BatchPayloadKind::classify(&[0x50]), Ok(Blob));
+        assert_eq!(BatchPayloadKind::classify(&[0x20]), Ok(Zeroheavy));
+        assert_eq!(BatchPayloadKind::classify(&[0x01]), Ok(Unknown(0x01)));
+        assert_eq!(
+            BatchPayloadKind::classify(&[0x41]),
+            Err(BatchError::UnsupportedHeaderByte(0x41))
+        );
+    }
+
+    #[cfg(feature = "brotli")]
+    fn brotli_batch(header: &BatchHeader, segments: &[Vec<u8>]) -> Vec<u8> {
+        let mut rlp = Vec::new();
+        for seg in segments {
+            alloy_rlp::Encodable::encode(&seg[..], &mut rlp);
+        }
+        let compressed = brotli_compress(&rlp, BROTLI_MAX_LEVEL);
+        let mut data = header.encode().to_vec();
+        data.push(BROTLI_MESSAGE_HEADER_BYTE);
+        data.extend_from_slice(&compressed);
+        data
+    }
+
+    #[cfg(feature = "brotli")]
+    fn advance(kind: u8, by: u64) -> Vec<u8> {
+        let mut seg = alloc::vec![kind];
+        alloy_rlp::Encodable::encode(&by, &mut seg);
+        seg
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn decodes_messages_with_advances_and_delayed_pointers() {
+        let segments = alloc::vec![
+            alloc::vec![BATCH_SEGMENT_KIND_L2_MESSAGE, 4, 0xc0],
+            advance(BATCH_SEGMENT_KIND_ADVANCE_TIMESTAMP, 12),
+            advance(BATCH_SEGMENT_KIND_ADVANCE_L1_BLOCK_NUMBER, 2),
+            alloc::vec![BATCH_SEGMENT_KIND_DELAYED_MESSAGES],
+            advance(BATCH_SEGMENT_KIND_ADVANCE_TIMESTAMP, 500),
+            alloc::vec![BATCH_SEGMENT_KIND_L2_MESSAGE, 6],
+        ];
+        let batch = decode_sequencer_batch(&brotli_batch(&header(), &segments), 1).unwrap();
+        assert_eq!(batch.payload_kind, BatchPayloadKind::Brotli);
+        assert_eq!(batch.segments.len(), 6);
+        assert_eq!(
+            batch.messages,
+            alloc::vec![
+                BatchMessage::L2 {
+                    timestamp: 1_000,
+                    l1_block_number: 50,
+                    data: Bytes::from_static(&[4, 0xc0]),
+                    delayed_messages_read: 1,
+                },
+                BatchMessage::Delayed { index: 1 },
+                BatchMessage::L2 {
+                    timestamp: 1_100,
+                    l1_block_number: 52,
+                    data: Bytes::from_static(&[6]),
+                    delayed_messages_read: 2,
+                },
+                BatchMessage::Delayed { index: 2 },
+            ]
+        );
+        let incoming = batch.messages[0].to_incoming_message().unwrap();
+        assert_eq!(incoming.header.poster, BATCH_POSTER_ADDRESS);
+        assert_eq!(incoming.header.request_id, None);
+        assert!(batch.messages[1].to_incoming_message().is_none());
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn reading_past_delayed_count_is_invalid() {
+        let segments = alloc::vec![alloc::vec![BATCH_SEGMENT_KIND_DELAYED_MESSAGES]];
+        let batch = decode_sequencer_batch(&brotli_batch(&header(), &segments), 3).unwrap();
+        assert_eq!(
+            batch.messages,
+            alloc::vec![BatchMessage::Invalid {
+                delayed_messages_read: 3
+            }]
+        );
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn compressed_l2_segments_are_inflated() {
+        let compressed = brotli_compress(&[4, 0xc0], BROTLI_MAX_LEVEL);
+        let mut seg = alloc::vec![BATCH_SEGMENT_KIND_L2_MESSAGE_BROTLI];
+        seg.extend_from_slice(&compressed);
+        let garbage = alloc::vec![BATCH_SEGMENT_KIND_L2_MESSAGE_BROTLI, 0xff, 0xff];
+        let mut h = header();
+        h.after_delayed_messages = 0;
+        let batch = decode_sequencer_batch(&brotli_batch(&h, &[seg, garbage]), 0).unwrap();
+        let BatchMessage::L2 { data, .. } = &batch.messages[0] else {
+            panic!("expected L2 message");
+        };
+        assert_eq!(data, &Bytes::from_static(&[4, 0xc0]));
+        // A segment that fails to decompress still takes its message slot.
+        assert_eq!(
+            batch.messages[1],
+            BatchMessage::Invalid {
+                delayed_messages_read: 0
+            }
+        );
+        assert_eq!(batch.messages.len(), 2);
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn unknown_segments_are_invalid_until_the_batch_ends() {
+        let mut h = header();
+        h.after_delayed_messages = 1;
+        let segments = alloc::vec![
+            alloc::vec![0x7f],
+            alloc::vec![BATCH_SEGMENT_KIND_L2_MESSAGE, 6],
+            alloc::vec![0x7f],
+        ];
+        let batch = decode_sequencer_batch(&brotli_batch(&h, &segments), 0).unwrap();
+        assert_eq!(
+            batch.messages,
+            alloc::vec![
+                BatchMessage::Invalid {
+                    delayed_messages_read: 1
+                },
+                BatchMessage::L2 {
+                    timestamp: 1_000,
+                    l1_block_number: 50,
+                    data: Bytes::from_static(&[6]),
+                    delayed_messages_read: 0,
+                },
+                // Still inside the batch while a delayed message is unread.
+                BatchMessage::Invalid {
+                    delayed_messages_read: 1
+                },
+                BatchMessage::Delayed { index: 0 },
+            ]
+        );
+
+        // Trailing unknown segments don't extend a finished batch.
+        h.after_delayed_messages = 0;
+        let batch = decode_sequencer_batch(&brotli_batch(&h, &segments), 0).unwrap();
+        assert_eq!(batch.messages.len(), 2);
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn empty_batch_sequences_one_invalid_message() {
+        let batch = decode_sequencer_batch(&brotli_batch(&header(), &[]), 3).unwrap();
+        assert_eq!(
+            batch.messages,
+            alloc::vec![BatchMessage::Invalid {
+                delayed_messages_read: 3
+            }]
+        );
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn external_and_broken_payloads() {
+        let mut data = header().encode().to_vec();
+        data.push(DAS_MESSAGE_HEADER_FLAG);
+        assert_eq!(
+            decode_sequencer_batch(&data, 0),
+            Err(BatchError::ExternalPayload(BatchPayloadKind::Das))
+        );
+        let mut data = header().encode().to_vec();
+        data.extend_from_slice(&[ZEROHEAVY_MESSAGE_HEADER_FLAG, 0x80]);
+        assert_eq!(
+            decode_sequencer_batch(&data, 0),
+            Err(BatchError::UnsupportedZeroheavy)
+        );
+
+        let mut data = header().encode().to_vec();
+        data.extend_from_slice(&[BROTLI_MESSAGE_HEADER_BYTE, 0xff, 0xff, 0xff]);
+        let batch = decode_sequencer_batch(&data, 0).unwrap();
+        assert!(batch.segments.is_empty());
+        assert_eq!(
+            batch.messages,
+            (0..3)
+                .map(|index| BatchMessage::Delayed { index })
+                .collect::<Vec<_>>()
+        );
+    }
+}
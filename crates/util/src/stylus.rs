@@ -0,0 +1,514 @@
+#![allow(dead_code)]
+
+use alloy_primitives::U256;
+use arb_alloy_consensus::ArbOsVersion;
+use thiserror::Error;
+
+use crate::l1_pricing::ONE_IN_BIPS;
+use crate::l2_pricing::approx_exp_basis_points;
+
+pub const WASM_PAGE_SIZE: u64 = 65_536;
+pub const MIN_INIT_GAS_UNITS: u64 = 128;
+pub const MIN_CACHED_GAS_UNITS: u64 = 32;
+pub const COST_SCALAR_PERCENT: u64 = 2;
+
+pub const INITIAL_STYLUS_VERSION: u16 = 1;
+pub const INITIAL_INK_PRICE: u32 = 10_000;
+pub const INITIAL_MAX_STACK_DEPTH: u32 = 4 * 65_536;
+pub const INITIAL_FREE_PAGES: u16 = 2;
+pub const INITIAL_PAGE_GAS: u16 = 1_000;
+pub const INITIAL_PAGE_RAMP: u64 = 620_674_314;
+pub const INITIAL_PAGE_LIMIT: u16 = 128;
+pub const INITIAL_MIN_INIT_GAS: u8 = 72;
+pub const INITIAL_MIN_CACHED_INIT_GAS: u8 = 11;
+pub const INITIAL_INIT_COST_SCALAR: u8 = 50;
+pub const INITIAL_CACHED_COST_SCALAR: u8 = 50;
+pub const INITIAL_EXPIRY_DAYS: u16 = 365;
+pub const INITIAL_KEEPALIVE_DAYS: u16 = 31;
+pub const INITIAL_BLOCK_CACHE_SIZE: u16 = 32;
+pub const INITIAL_MAX_WASM_SIZE: u32 = 128 * 1024;
+
+/// When Arbitrum began; program activation times count hours from here.
+pub const ARBITRUM_START_TIME: u64 = 1_421_388_000;
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub const INITIAL_DATA_DEMAND: u32 = 0;
+/// A 1TB footprint a year.
+pub const INITIAL_HOURLY_BYTES: u64 = (1 << 40) / (365 * 24);
+pub const INITIAL_DATA_BYTES_PER_SECOND: u32 = (INITIAL_HOURLY_BYTES / (60 * 60)) as u32;
+/// 5MB for $1.
+pub const INITIAL_DATA_MIN_PRICE: u32 = 82_928_201;
+/// Expensive at 1TB.
+pub const INITIAL_DATA_INERTIA: u32 = 21_360_419;
+
+/// Nitro's `memoryExponents`: `floor(b^p)` for `b = 31_874_167^(1/128)`, so
+/// that ever touching all 128 pages costs about 32M gas on top of the
+/// linear page price.
+const MEMORY_EXPONENTS: [u32; 129] = [
+    1, 1, 1, 1, 1, 1, 2, 2, 2, 3, 3, 4, 5, 5, 6, 7, 8, 9, 11, 12, 14, 17, 19, 22, 25, 29, 33, 38,
+    43, 50, 57, 65, 75, 85, 98, 112, 128, 147, 168, 193, 221, 253, 289, 331, 379, 434, 497, 569,
+    651, 745, 853, 976, 1117, 1279, 1463, 1675, 1917, 2194, 2511, 2874, 3290, 3765, 4310, 4932,
+    5645, 6461, 7395, 8464, 9687, 11087, 12689, 14523, 16622, 19024, 21773, 24919, 28521, 32642,
+    37360, 42759, 48938, 56010, 64104, 73368, 83971, 96106, 109995, 125890, 144083, 164905, 188736,
+    216011, 247227, 282954, 323845, 370645, 424208, 485511, 555674, 635976, 727883, 833071, 953460,
+    1091247, 1248947, 1429435, 1636007, 1872431, 2143021, 2452715, 2807163, 3212834, 3677130,
+    4208522, 4816706, 5512782, 6309449, 7221244, 8264806, 9459175, 10826146, 12390662, 14181270,
+    16230644, 18576178, 21260672, 24333109, 27849552, 31874167,
+];
+
+/// A snapshot of the ArbWasm parameters, as `ArbWasm`'s getters return them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StylusParams {
+    pub version: u16,
+    /// Ink per unit of gas.
+    pub ink_price: u32,
+    pub max_stack_depth: u32,
+    pub free_pages: u16,
+    pub page_gas: u16,
+    /// Only reported; the page ramp is baked into `MEMORY_EXPONENTS`.
+    pub page_ramp: u64,
+    pub page_limit: u16,
+    /// In units of `MIN_INIT_GAS_UNITS`.
+    pub min_init_gas: u8,
+    /// In units of `MIN_CACHED_GAS_UNITS`.
+    pub min_cached_init_gas: u8,
+    /// In units of `COST_SCALAR_PERCENT` percent.
+    pub init_cost_scalar: u8,
+    /// In units of `COST_SCALAR_PERCENT` percent.
+    pub cached_cost_scalar: u8,
+    pub expiry_days: u16,
+    pub keepalive_days: u16,
+    pub block_cache_size: u16,
+    pub max_wasm_size: u32,
+}
+
+impl Default for StylusParams {
+    fn default() -> Self {
+        Self {
+            version: INITIAL_STYLUS_VERSION,
+            ink_price: INITIAL_INK_PRICE,
+            max_stack_depth: INITIAL_MAX_STACK_DEPTH,
+            free_pages: INITIAL_FREE_PAGES,
+            page_gas: INITIAL_PAGE_GAS,
+            page_ramp: INITIAL_PAGE_RAMP,
+            page_limit: INITIAL_PAGE_LIMIT,
+            min_init_gas: INITIAL_MIN_INIT_GAS,
+            min_cached_init_gas: INITIAL_MIN_CACHED_INIT_GAS,
+            init_cost_scalar: INITIAL_INIT_COST_SCALAR,
+            cached_cost_scalar: INITIAL_CACHED_COST_SCALAR,
+            expiry_days: INITIAL_EXPIRY_DAYS,
+            keepalive_days: INITIAL_KEEPALIVE_DAYS,
+            block_cache_size: INITIAL_BLOCK_CACHE_SIZE,
+            max_wasm_size: INITIAL_MAX_WASM_SIZE,
+        }
+    }
+}
+
+impl StylusParams {
+    pub fn gas_to_ink(&self, gas: u64) -> u64 {
+        gas.saturating_mul(u64::from(self.ink_price))
+    }
+
+    /// Rounds down, as ArbOS does when a program returns its leftover ink.
+    pub fn ink_to_gas(&self, ink: u64) -> u64 {
+        ink / u64::from(self.ink_price.max(1))
+    }
+
+    /// Nitro's `MemoryModel.GasCost`: the gas to open `pages` more pages in a
+    /// tx that has `open` pages open and has had up to `ever` open at once.
+    /// The first `free_pages` are free, then each page costs `page_gas` plus
+    /// an exponential charge whenever the high-water mark rises.
+    pub fn memory_gas_cost(&self, pages: u16, open: u16, ever: u16) -> u64 {
+        let new_open = open.saturating_add(pages);
+        let new_ever = ever.max(new_open);
+        if new_ever <= self.free_pages {
+            return 0;
+        }
+        let sub_free = |pages: u16| pages.saturating_sub(self.free_pages);
+        let adding = sub_free(new_open).saturating_sub(sub_free(open));
+        let linear = u64::from(adding).saturating_mul(u64::from(self.page_gas));
+        let expand = memory_exp(new_ever).saturating_sub(memory_exp(ever));
+        linear.saturating_add(expand)
+    }
+
+    /// The minimum gas a call to an uncached program pays to instantiate it.
+    pub fn min_init_gas(&self) -> u64 {
+        u64::from(self.min_init_gas) * MIN_INIT_GAS_UNITS
+    }
+
+    /// The minimum gas a call to a cached program pays to instantiate it.
+    pub fn min_cached_init_gas(&self) -> u64 {
+        u64::from(self.min_cached_init_gas) * MIN_CACHED_GAS_UNITS
+    }
+}
+
+/// ArbOS's `DataPricer`, which prices the bytes of compiled programs that
+/// activations and keepalives add to nodes' disks. Demand decays at
+/// `bytes_per_second` and the price per byte grows exponentially in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataPricer {
+    pub demand: u32,
+    pub bytes_per_second: u32,
+    pub last_update_time: u64,
+    pub min_price: u32,
+    pub inertia: u32,
+}
+
+impl Default for DataPricer {
+    fn default() -> Self {
+        Self {
+            demand: INITIAL_DATA_DEMAND,
+            bytes_per_second: INITIAL_DATA_BYTES_PER_SECOND,
+            last_update_time: ARBITRUM_START_TIME,
+            min_price: INITIAL_DATA_MIN_PRICE,
+            inertia: INITIAL_DATA_INERTIA,
+        }
+    }
+}
+
+impl DataPricer {
+    /// Nitro's `UpdateModel`: records `temp_bytes` of demand at `time` and
+    /// returns their fee in wei.
+    pub fn update_model(&mut self, temp_bytes: u32, time: u64) -> Result<U256, StylusError> {
+        if self.inertia == 0 {
+            return Err(StylusError::DataInertiaZero);
+        }
+        let passed = u32::try_from(time.saturating_sub(self.last_update_time)).unwrap_or(u32::MAX);
+        let credit = self.bytes_per_second.saturating_mul(passed);
+        self.demand = self
+            .demand
+            .saturating_sub(credit)
+            .saturating_add(temp_bytes);
+        self.last_update_time = time;
+
+        let exponent = ONE_IN_BIPS as i64 * i64::from(self.demand) / i64::from(self.inertia);
+        let multiplier = approx_exp_basis_points(exponent, 12) as u64;
+        let cost_per_byte = u64::from(self.min_price).saturating_mul(multiplier) / ONE_IN_BIPS;
+        Ok(U256::from(
+            cost_per_byte.saturating_mul(u64::from(temp_bytes)),
+        ))
+    }
+
+    /// The data fee an activation at `time` would pay for a program whose
+    /// compiled size is estimated at `asm_size` bytes, without recording it.
+    pub fn activation_data_fee(&self, asm_size: u32, time: u64) -> Result<U256, StylusError> {
+        self.clone().update_model(asm_size, time)
+    }
+}
+
+/// Whole hours from Arbitrum's start to `time`, as activations record it.
+pub fn hours_since_arbitrum(time: u64) -> u32 {
+    let hours = time.saturating_sub(ARBITRUM_START_TIME) / 3600;
+    u32::try_from(hours).unwrap_or(u32::MAX).min(MAX_U24)
+}
+
+const MAX_U24: u32 = (1 << 24) - 1;
+
+fn memory_exp(pages: u16) -> u64 {
+    MEMORY_EXPONENTS
+        .get(usize::from(pages))
+        .map_or(u64::MAX, |exp| u64::from(*exp))
+}
+
+fn scaled_cost(base: u64, cost: u16, scalar: u8) -> u64 {
+    let dyno = u64::from(cost).saturating_mul(u64::from(scalar) * COST_SCALAR_PERCENT);
+    base.saturating_add(dyno.div_ceil(100))
+}
+
+/// The per-program data ArbOS records at activation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StylusProgram {
+    pub version: u16,
+    pub init_cost: u16,
+    pub cached_cost: u16,
+    /// The pages the program opens when it starts.
+    pub footprint: u16,
+    pub asm_estimate_kb: u32,
+    /// Hours since Arbitrum began.
+    pub activated_at: u32,
+    pub cached: bool,
+}
+
+impl StylusProgram {
+    /// What `programInitGas` reports as the uncached init gas.
+    pub fn init_gas(&self, params: &StylusParams) -> u64 {
+        scaled_cost(
+            params.min_init_gas(),
+            self.init_cost,
+            params.init_cost_scalar,
+        )
+    }
+
+    /// What `programInitGas` reports as the cached init gas.
+    pub fn cached_gas(&self, params: &StylusParams) -> u64 {
+        scaled_cost(
+            params.min_cached_init_gas(),
+            self.cached_cost,
+            params.cached_cost_scalar,
+        )
+    }
+
+    /// Seconds at `time` since the program was activated or last kept alive.
+    pub fn age_seconds(&self, time: u64) -> u64 {
+        let activated_at =
+            ARBITRUM_START_TIME.saturating_add(u64::from(self.activated_at).saturating_mul(3600));
+        time.saturating_sub(activated_at)
+    }
+
+    /// The estimated compiled size keepalives are charged for.
+    pub fn asm_size(&self) -> u32 {
+        self.asm_estimate_kb.saturating_mul(1024)
+    }
+
+    /// ArbOS's `getActiveProgram` checks: Stylus enabled, activated, on the
+    /// current Stylus version and not expired.
+    pub fn check_active(
+        &self,
+        arbos_version: ArbOsVersion,
+        params: &StylusParams,
+        time: u64,
+    ) -> Result<(), StylusError> {
+        if !arbos_version.has_stylus() {
+            return Err(StylusError::StylusNotEnabled);
+        }
+        if self.version == 0 {
+            return Err(StylusError::ProgramNotActivated);
+        }
+        if self.version != params.version {
+            return Err(StylusError::ProgramNeedsUpgrade {
+                version: self.version,
+                stylus_version: params.version,
+            });
+        }
+        let age = self.age_seconds(time);
+        if age > u64::from(params.expiry_days) * SECONDS_PER_DAY {
+            return Err(StylusError::ProgramExpired { age });
+        }
+        Ok(())
+    }
+
+    /// What `programTimeLeft` returns: the seconds until the program expires.
+    pub fn time_left(
+        &self,
+        arbos_version: ArbOsVersion,
+        params: &StylusParams,
+        time: u64,
+    ) -> Result<u64, StylusError> {
+        self.check_active(arbos_version, params, time)?;
+        Ok(
+            (u64::from(params.expiry_days) * SECONDS_PER_DAY)
+                .saturating_sub(self.age_seconds(time)),
+        )
+    }
+
+    /// Whether `codehashKeepalive` would succeed at `time`: the program must
+    /// be active and at least `keepalive_days` old.
+    pub fn check_keepalive(
+        &self,
+        arbos_version: ArbOsVersion,
+        params: &StylusParams,
+        time: u64,
+    ) -> Result<(), StylusError> {
+        self.check_active(arbos_version, params, time)?;
+        let age = self.age_seconds(time);
+        if age < u64::from(params.keepalive_days) * SECONDS_PER_DAY {
+            return Err(StylusError::KeepaliveTooSoon { age });
+        }
+        Ok(())
+    }
+
+    /// A keepalive at `time`: charges the program's data fee and resets its
+    /// activation time.
+    pub fn keepalive(
+        &mut self,
+        arbos_version: ArbOsVersion,
+        params: &StylusParams,
+        pricer: &mut DataPricer,
+        time: u64,
+    ) -> Result<U256, StylusError> {
+        self.check_keepalive(arbos_version, params, time)?;
+        let fee = pricer.update_model(self.asm_size(), time)?;
+        self.activated_at = hours_since_arbitrum(time);
+        Ok(fee)
+    }
+
+    /// The gas charged before a call enters the program: its footprint's
+    /// memory and its init cost. `cached` covers both the cache manager's
+    /// cache and a hit in the block's recent-program cache. Version 1
+    /// programs fold the cached cost into the init cost.
+    pub fn call_gas(&self, params: &StylusParams, open: u16, ever: u16, cached: bool) -> u64 {
+        let cached = cached || self.cached;
+        let mut gas = params.memory_gas_cost(self.footprint, open, ever);
+        if cached || self.version > 1 {
+            gas = gas.saturating_add(self.cached_gas(params));
+        }
+        if !cached {
+            gas = gas.saturating_add(self.init_gas(params));
+        }
+        gas
+    }
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum StylusError {
+    #[error("stylus is not enabled at this ArbOS version")]
+    StylusNotEnabled,
+    #[error("program not activated")]
+    ProgramNotActivated,
+    #[error("program needs upgrade: version {version}, stylus version {stylus_version}")]
+    ProgramNeedsUpgrade { version: u16, stylus_version: u16 },
+    #[error("program expired: age {age}s")]
+    ProgramExpired { age: u64 },
+    #[error("keepalive too soon: age {age}s")]
+    KeepaliveTooSoon { age: u64 },
+    #[error("data pricer inertia is zero")]
+    DataInertiaZero,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_ink_and_gas() {
+        let params = StylusParams::default();
+        assert_eq!(params.gas_to_ink(3), 30_000);
+        assert_eq!(params.ink_to_gas(29_999), 2);
+        assert_eq!(params.gas_to_ink(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn memory_is_free_then_ramps() {
+        let params = StylusParams::default();
+        assert_eq!(params.memory_gas_cost(2, 0, 0), 0);
+        // Past the free pages each page costs its linear price, plus the ramp.
+        assert_eq!(params.memory_gas_cost(1, 2, 2), 1_000);
+        assert_eq!(params.memory_gas_cost(4, 2, 2), 4_000 + 2 - 1);
+        // Reopening pages under the high-water mark is linear only.
+        assert_eq!(params.memory_gas_cost(4, 0, 6), 2_000);
+        assert_eq!(params.memory_gas_cost(128, 0, 0), 126_000 + 31_874_166);
+        // Pages beyond the table are priced out.
+        assert_eq!(
+            params.memory_gas_cost(1, 128, 128),
+            u64::MAX - 31_874_167 + 1_000
+        );
+    }
+
+    #[test]
+    fn exponents_follow_the_ramp() {
+        assert_eq!(MEMORY_EXPONENTS[0], 1);
+        assert!(MEMORY_EXPONENTS.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(memory_exp(128), 31_874_167);
+        assert_eq!(memory_exp(129), u64::MAX);
+    }
+
+    #[test]
+    fn init_gas_scales_with_program_costs() {
+        let params = StylusParams::default();
+        let program = StylusProgram {
+            version: 1,
+            init_cost: 1_001,
+            cached_cost: 300,
+            footprint: 1,
+            ..Default::default()
+        };
+        // 1001 * 50 * 2% rounds up to 1001.
+        assert_eq!(program.init_gas(&params), 72 * 128 + 1_001);
+        assert_eq!(program.cached_gas(&params), 11 * 32 + 300);
+
+        assert_eq!(program.call_gas(&params, 0, 0, false), 9_216 + 1_001);
+        assert_eq!(program.call_gas(&params, 0, 0, true), 352 + 300);
+        let v2 = StylusProgram {
+            version: 2,
+            ..program
+        };
+        assert_eq!(
+            v2.call_gas(&params, 2, 2, false),
+            1_000 + 352 + 300 + 9_216 + 1_001
+        );
+    }
+
+    #[test]
+    fn data_fee_grows_with_demand_and_decays() {
+        let mut pricer = DataPricer::default();
+        let start = ARBITRUM_START_TIME + 1_000 * SECONDS_PER_DAY;
+        let first = pricer.activation_data_fee(100_000, start).unwrap();
+        // The bytes' own demand lifts the price 46 bips above the minimum.
+        assert_eq!(first, U256::from(82_928_201u64 * 10_046 / 10_000 * 100_000));
+        assert_eq!(pricer, DataPricer::default());
+
+        let fee = pricer.update_model(20_000_000, start).unwrap();
+        let busy = pricer.activation_data_fee(100_000, start).unwrap();
+        assert!(fee > U256::ZERO && busy > first);
+        let later = start + u64::from(20_000_000 / INITIAL_DATA_BYTES_PER_SECOND) + 1;
+        assert_eq!(pricer.activation_data_fee(100_000, later).unwrap(), first);
+
+        pricer.inertia = 0;
+        assert_eq!(
+            pricer.update_model(1, later),
+            Err(StylusError::DataInertiaZero)
+        );
+    }
+
+    #[test]
+    fn programs_expire_unless_kept_alive() {
+        let version = ArbOsVersion::STYLUS;
+        let params = StylusParams::default();
+        let activated = ARBITRUM_START_TIME + 3600 * 100 + 59;
+        let mut program = StylusProgram {
+            version: 1,
+            asm_estimate_kb: 10,
+            activated_at: hours_since_arbitrum(activated),
+            ..Default::default()
+        };
+        assert_eq!(program.activated_at, 100);
+        let expiry = 365 * SECONDS_PER_DAY;
+        assert_eq!(
+            program.time_left(version, &params, activated),
+            Ok(expiry - 59)
+        );
+
+        let soon = activated + 30 * SECONDS_PER_DAY;
+        assert_eq!(
+            program.check_keepalive(version, &params, soon),
+            Err(StylusError::KeepaliveTooSoon {
+                age: 30 * SECONDS_PER_DAY + 59
+            })
+        );
+        let expired = activated + expiry;
+        assert_eq!(
+            program.time_left(version, &params, expired),
+            Err(StylusError::ProgramExpired { age: expiry + 59 })
+        );
+
+        let due = activated + 31 * SECONDS_PER_DAY;
+        let mut pricer = DataPricer::default();
+        let fee = program
+            .keepalive(version, &params, &mut pricer, due)
+            .unwrap();
+        assert_eq!(fee, U256::from(82_928_201u64 * 10_004 / 10_000 * 10 * 1024));
+        assert_eq!(program.time_left(version, &params, due), Ok(expiry - 59));
+        assert!(program.check_keepalive(version, &params, due).is_err());
+
+        let upgraded = StylusParams {
+            version: 2,
+            ..params
+        };
+        assert_eq!(
+            program.time_left(version, &upgraded, due),
+            Err(StylusError::ProgramNeedsUpgrade {
+                version: 1,
+                stylus_version: 2
+            })
+        );
+        assert_eq!(
+            program.check_active(ArbOsVersion::V20, &params, due),
+            Err(StylusError::StylusNotEnabled)
+        );
+        program.version = 0;
+        assert_eq!(
+            program.check_active(version, &params, due),
+            Err(StylusError::ProgramNotActivated)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+#![allow(dead_code)]
+
+#[cfg(feature = "brotli")]
+use alloc::vec::Vec;
+use alloy_primitives::{keccak256, B256};
+use thiserror::Error;
+
+#[cfg(feature = "brotli")]
+use crate::compression::{brotli_compress, brotli_decompress, BROTLI_MAX_LEVEL};
+
+/// The EOF-reserved prefix that marks contract code as a Stylus program.
+pub const STYLUS_DISCRIMINANT: [u8; 3] = [0xef, 0xf0, 0x00];
+pub const STYLUS_PREFIX_LEN: usize = STYLUS_DISCRIMINANT.len() + 1;
+pub const WASM_MAGIC: [u8; 4] = *b"\0asm";
+
+/// The brotli dictionary the compressed wasm was encoded against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StylusDictionary {
+    Empty,
+    /// Nitro's built-in dictionary tuned for Stylus programs.
+    StylusProgram,
+}
+
+impl StylusDictionary {
+    pub fn from_u8(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Empty),
+            1 => Some(Self::StylusProgram),
+            _ => None,
+        }
+    }
+
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Empty => 0,
+            Self::StylusProgram => 1,
+        }
+    }
+}
+
+/// Nitro's `IsStylusProgram`: the discriminant followed by at least the
+/// dictionary byte.
+pub fn is_stylus_code(code: &[u8]) -> bool {
+    code.len() >= STYLUS_PREFIX_LEN && code.starts_with(&STYLUS_DISCRIMINANT)
+}
+
+/// The hash `codehashVersion`, `codehashAsmSize` and friends take: the
+/// account's code hash, prefix included.
+pub fn stylus_code_hash(code: &[u8]) -> B256 {
+    keccak256(code)
+}
+
+/// Stylus contract code split into its prefix and compressed wasm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StylusCode<'a> {
+    pub dictionary: StylusDictionary,
+    pub compressed_wasm: &'a [u8],
+}
+
+impl<'a> StylusCode<'a> {
+    /// Nitro's `StripStylusPrefix`.
+    pub fn parse(code: &'a [u8]) -> Result<Self, StylusCodeError> {
+        if !is_stylus_code(code) {
+            return Err(StylusCodeError::NotStylus);
+        }
+        let byte = code[STYLUS_DISCRIMINANT.len()];
+        let dictionary =
+            StylusDictionary::from_u8(byte).ok_or(StylusCodeError::UnknownDictionary(byte))?;
+        Ok(Self {
+            dictionary,
+            compressed_wasm: &code[STYLUS_PREFIX_LEN..],
+        })
+    }
+
+    /// Decompresses the wasm, failing if it exceeds `max_wasm_size` (the
+    /// `StylusParams::max_wasm_size` activation enforces). Only the empty
+    /// dictionary is supported, as Nitro's Stylus dictionary isn't bundled.
+    #[cfg(feature = "brotli")]
+    pub fn wasm(&self, max_wasm_size: usize) -> Result<Vec<u8>, StylusCodeError> {
+        if self.dictionary != StylusDictionary::Empty {
+            return Err(StylusCodeError::UnsupportedDictionary(self.dictionary));
+        }
+        let wasm = brotli_decompress(self.compressed_wasm, max_wasm_size)
+            .ok_or(StylusCodeError::DecompressionFailed)?;
+        if !wasm.starts_with(&WASM_MAGIC) {
+            return Err(StylusCodeError::NotWasm);
+        }
+        Ok(wasm)
+    }
+}
+
+/// Deployable Stylus code for `wasm`, compressed against the empty
+/// dictionary at brotli's highest quality as `cargo stylus` does.
+#[cfg(feature = "brotli")]
+pub fn encode_stylus_code(wasm: &[u8]) -> Vec<u8> {
+    let mut code = STYLUS_DISCRIMINANT.to_vec();
+    code.push(StylusDictionary::Empty.as_u8());
+    code.extend_from_slice(&brotli_compress(wasm, BROTLI_MAX_LEVEL));
+    code
+}
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum StylusCodeError {
+    #[error("code is not a Stylus program")]
+    NotStylus,
+    #[error("unknown Stylus dictionary {0}")]
+    UnknownDictionary(u8),
+    #[error("unsupported Stylus dictionary {0:?}")]
+    UnsupportedDictionary(StylusDictionary),
+    #[error("failed to decompress wasm within the size limit")]
+    DecompressionFailed,
+    #[error("decompressed code is not wasm")]
+    NotWasm,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_and_parses_the_prefix() {
+        assert!(!is_stylus_code(&[0x60, 0x80, 0x60, 0x40]));
+        assert!(!is_stylus_code(&STYLUS_DISCRIMINANT));
+        assert!(is_stylus_code(&[0xef, 0xf0, 0x00, 0x00]));
+
+        let code = [0xef, 0xf0, 0x00, 0x01, 0xaa, 0xbb];
+        let parsed = StylusCode::parse(&code).unwrap();
+        assert_eq!(parsed.dictionary, StylusDictionary::StylusProgram);
+        assert_eq!(parsed.compressed_wasm, &[0xaa, 0xbb]);
+        assert_eq!(
+            StylusCode::parse(&[0xef, 0xf0, 0x00, 0x07]),
+            Err(StylusCodeError::UnknownDictionary(7))
+        );
+        assert_eq!(StylusCode::parse(&[0x00]), Err(StylusCodeError::NotStylus));
+        assert_eq!(stylus_code_hash(&code), keccak256(code));
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn round_trips_compressed_wasm() {
+        let mut wasm = WASM_MAGIC.to_vec();
+        wasm.extend_from_slice(&[1, 0, 0, 0]);
+        wasm.resize(4_096, 0);
+        let code = encode_stylus_code(&wasm);
+        assert!(is_stylus_code(&code) && code.len() < wasm.len());
+
+        let parsed = StylusCode::parse(&code).unwrap();
+        assert_eq!(parsed.wasm(4_096).unwrap(), wasm);
+        assert_eq!(
+            parsed.wasm(4_095),
+            Err(StylusCodeError::DecompressionFailed)
+        );
+
+        let mut evm = STYLUS_DISCRIMINANT.to_vec();
+        evm.push(0);
+        evm.extend_from_slice(&brotli_compress(&[0x60; 64], BROTLI_MAX_LEVEL));
+        assert_eq!(
+            StylusCode::parse(&evm).unwrap().wasm(4_096),
+            Err(StylusCodeError::NotWasm)
+        );
+        let mut dict = code.clone();
+        dict[3] = 1;
+        assert_eq!(
+            StylusCode::parse(&dict).unwrap().wasm(4_096),
+            Err(StylusCodeError::UnsupportedDictionary(
+                StylusDictionary::StylusProgram
+            ))
+        );
+    }
+}